@@ -1,10 +1,29 @@
-use std::{any::Any, collections::BTreeMap};
+use std::{
+    any::{Any, TypeId},
+    collections::BTreeMap,
+    ops::Range,
+    sync::Arc,
+};
 
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{RootSchema, Schema, SchemaObject, SubschemaValidation},
+    JsonSchema,
+};
 use serde::{de::DeserializeOwned, Serialize};
 use serde_json::Value;
-use smash::lua2cpp::L2CAgentBase;
 use thiserror::Error;
 
+use crate::{
+    context::ExecutionContext,
+    diagnostic::{Diagnostics, JsonPointer, PathSegment},
+    metadata::{ActionInfo, ActionMetadata},
+    observer::ExecutionObserver,
+    schema,
+    timeline::{MotionRate, Wait},
+    SmashlineScript,
+};
+
 #[repr(C)]
 #[derive(Error, Debug)]
 pub enum ActionError {
@@ -14,6 +33,12 @@ pub enum ActionError {
     #[error("{0}")]
     SerializeError(serde_json::Error),
 
+    #[error("{0}")]
+    DecodeError(crate::binary::DecodeError),
+
+    #[error("{0}")]
+    TextError(crate::dsl::ParseError),
+
     #[error("Action '{name}' is not registered")]
     NotRegistered { name: String },
 
@@ -40,6 +65,84 @@ pub enum ActionError {
         name: &'static str,
         error: serde_json::Error,
     },
+
+    #[error("Call to '{target}' was never linked to a loaded script")]
+    UnlinkedCall { target: String },
+
+    #[error("{}: {error}", JsonPointer(path))]
+    Nested {
+        path: Vec<PathSegment>,
+        error: Box<ActionError>,
+    },
+
+    #[error("{} errors{}", .0.len(), first_error(.0))]
+    Multiple(Vec<ActionError>),
+
+    #[error("{0}")]
+    InvalidActions(Diagnostics),
+
+    #[error("Patch operation {operation} failed: {error}")]
+    PatchError {
+        operation: usize,
+        error: crate::patch::PatchError,
+    },
+
+    #[error("{0}")]
+    TemplateError(crate::template::TemplateError),
+
+    #[error("In template '{template}': {error}")]
+    InTemplate {
+        template: String,
+        error: Box<ActionError>,
+    },
+}
+
+/// The first of several errors for the message of [`ActionError::Multiple`]
+fn first_error(errors: &[ActionError]) -> String {
+    match errors.first() {
+        Some(error) => format!(", first: {error}"),
+        None => String::new(),
+    }
+}
+
+impl ActionError {
+    fn at(self, segment: PathSegment) -> Self {
+        match self {
+            Self::Nested { mut path, error } => {
+                path.insert(0, segment);
+                Self::Nested { path, error }
+            }
+            Self::Multiple(errors) => Self::Multiple(
+                errors
+                    .into_iter()
+                    .map(|error| error.at(segment.clone()))
+                    .collect(),
+            ),
+            error => Self::Nested {
+                path: vec![segment],
+                error: Box::new(error),
+            },
+        }
+    }
+
+    /// Marks the error as coming from the given field of an action, such as the body of a loop
+    pub fn at_key(self, key: impl Into<String>) -> Self {
+        self.at(PathSegment::Key(key.into()))
+    }
+
+    /// Marks the error as coming from the action at `index` of an action list
+    pub fn at_index(self, index: usize) -> Self {
+        self.at(PathSegment::Index(index))
+    }
+
+    /// Collects every error in `errors`, returning `None` if there are none
+    pub fn from_errors(mut errors: Vec<ActionError>) -> Option<Self> {
+        match errors.len() {
+            0 => None,
+            1 => errors.pop(),
+            _ => Some(Self::Multiple(errors)),
+        }
+    }
 }
 
 #[repr(C)]
@@ -48,13 +151,166 @@ pub struct DynamicAction {
     pub action: Box<dyn Any + Send + Sync + 'static>,
 }
 
-pub trait Action: Serialize + DeserializeOwned + Clone + Send + Sync + 'static {
+pub trait Action: Serialize + DeserializeOwned + JsonSchema + Clone + Send + Sync + 'static {
+    const NAME: &'static str;
+
+    /// The name used for the action in the text syntax, such as `frame` or `WorkModule.on_flag`
+    const ACMD_NAME: &'static str = Self::NAME;
+
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext);
+
+    /// The JSON schema of the action's value, the one derived for it unless overridden
+    fn schema(generator: &mut SchemaGenerator) -> Schema {
+        generator.subschema_for::<Self>()
+    }
+
+    /// The Rust statements that do the same as [`Action::execute`], see [`codegen`](crate::codegen)
+    fn to_rust(&self) -> Option<String> {
+        None
+    }
+
+    /// How long the action makes the script wait, for actions which only wait
+    fn wait(&self) -> Option<Wait> {
+        None
+    }
+
+    /// Creates the action from a wait, used when turning a
+    /// [`Timeline`](crate::timeline::Timeline) back into actions
+    fn from_wait(_wait: Wait) -> Option<Self> {
+        None
+    }
+
+    /// How the action changes the speed the animation plays at, which the script's frames follow
+    fn motion_rate(&self) -> Option<MotionRate> {
+        None
+    }
+
+    /// Information for editors, anything left out is filled in from the schema, see
+    /// [`metadata`](crate::metadata)
+    fn metadata() -> ActionMetadata {
+        ActionMetadata::default()
+    }
+}
+
+/// An action which owns nested actions, such as a branch or a loop.
+///
+/// Control actions can't be converted with serde alone since their children are
+/// [`DynamicAction`]s, so they are handed the registry to convert and run them.
+pub trait ControlAction: Send + Sync + 'static {
     const NAME: &'static str;
 
-    unsafe fn execute(&self, context: &mut L2CAgentBase);
+    /// The name used for the action in the text syntax, see [`Action::ACMD_NAME`]
+    const ACMD_NAME: &'static str = Self::NAME;
+
+    fn from_value(registry: &ActionRegistry, value: Value) -> Result<Self, ActionError>
+    where
+        Self: Sized;
+
+    fn into_value(&self, registry: &ActionRegistry) -> Result<Value, ActionError>;
+
+    unsafe fn execute(
+        &self,
+        registry: &ActionRegistry,
+        context: &mut dyn ExecutionContext,
+    ) -> Result<(), ActionError>;
+
+    /// The nested action lists owned by this action, used to walk a script without running it
+    fn blocks(&self) -> Vec<&ActionBlock> {
+        vec![]
+    }
+
+    /// Whether running the action waits for at least one frame however it runs, such as a branch
+    /// that waits on both sides. Used to make sure that loops give control back to the game.
+    fn always_waits(&self, _registry: &ActionRegistry) -> bool {
+        false
+    }
+
+    /// Whether the action can wait in actions that aren't in its [`blocks`](Self::blocks), such as
+    /// the ones of another script, which leaves the frames after it unknown
+    fn waits_elsewhere(&self) -> bool {
+        false
+    }
+
+    /// The JSON schema of the action's value, see [`schema::action_list`] for nested actions
+    fn schema(_generator: &mut SchemaGenerator) -> Schema
+    where
+        Self: Sized,
+    {
+        Schema::Bool(true)
+    }
+
+    /// The Rust statements that do the same as this action, nested blocks are written with
+    /// [`codegen::block`](crate::codegen::block)
+    fn to_rust(&self, _registry: &ActionRegistry) -> Option<String> {
+        None
+    }
+
+    /// Information for editors, see [`Action::metadata`]
+    fn metadata() -> ActionMetadata
+    where
+        Self: Sized,
+    {
+        ActionMetadata::default()
+    }
 }
 
-fn into_value_impl<A: Action>(action: &DynamicAction) -> Result<Value, ActionError> {
+/// A list of actions nested inside of a [`ControlAction`]
+#[derive(Default)]
+pub struct ActionBlock(pub Vec<DynamicAction>);
+
+impl ActionBlock {
+    /// Converts every value into an action, reporting every action that fails instead of only the first
+    pub fn from_values(registry: &ActionRegistry, values: Vec<Value>) -> Result<Self, ActionError> {
+        let mut actions = Vec::with_capacity(values.len());
+        let mut errors = vec![];
+
+        for (index, value) in values.into_iter().enumerate() {
+            match registry.as_action(value) {
+                Ok(action) => actions.push(action),
+                Err(error) => errors.push(error.at_index(index)),
+            }
+        }
+
+        match ActionError::from_errors(errors) {
+            Some(error) => Err(error),
+            None => Ok(Self(actions)),
+        }
+    }
+
+    pub fn to_values(&self, registry: &ActionRegistry) -> Result<Vec<Value>, ActionError> {
+        self.0
+            .iter()
+            .map(|action| registry.as_value(action))
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Whether running the actions always waits for at least one frame, see
+    /// [`ActionRegistry::always_waits`]
+    pub fn always_waits(&self, registry: &ActionRegistry) -> bool {
+        self.0.iter().any(|action| registry.always_waits(action))
+    }
+
+    pub unsafe fn execute(
+        &self,
+        registry: &ActionRegistry,
+        context: &mut dyn ExecutionContext,
+    ) -> Result<(), ActionError> {
+        for action in self.0.iter() {
+            registry.execute(action, context)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn into_value_impl<A: Action>(
+    _: &ActionRegistry,
+    action: &DynamicAction,
+) -> Result<Value, ActionError> {
     let real_action =
         action
             .action
@@ -70,7 +326,10 @@ fn into_value_impl<A: Action>(action: &DynamicAction) -> Result<Value, ActionErr
     })
 }
 
-fn from_value_impl<A: Action>(value: Value) -> Result<DynamicAction, ActionError> {
+fn from_value_impl<A: Action>(
+    _: &ActionRegistry,
+    value: Value,
+) -> Result<DynamicAction, ActionError> {
     let value: A = serde_json::from_value(value).map_err(|e| ActionError::FromValueError {
         name: A::NAME,
         error: e,
@@ -83,8 +342,9 @@ fn from_value_impl<A: Action>(value: Value) -> Result<DynamicAction, ActionError
 }
 
 fn execute_impl<A: Action>(
+    _: &ActionRegistry,
     action: &DynamicAction,
-    context: &mut L2CAgentBase,
+    context: &mut dyn ExecutionContext,
 ) -> Result<(), ActionError> {
     let value = action
         .action
@@ -97,11 +357,250 @@ fn execute_impl<A: Action>(
     Ok(unsafe { value.execute(context) })
 }
 
+fn control_into_value_impl<C: ControlAction>(
+    registry: &ActionRegistry,
+    action: &DynamicAction,
+) -> Result<Value, ActionError> {
+    let real_action =
+        action
+            .action
+            .downcast_ref::<C>()
+            .ok_or_else(|| ActionError::InvalidType {
+                have: action.name.clone(),
+                expected: C::NAME,
+            })?;
+
+    real_action.into_value(registry)
+}
+
+fn control_from_value_impl<C: ControlAction>(
+    registry: &ActionRegistry,
+    value: Value,
+) -> Result<DynamicAction, ActionError> {
+    let value = C::from_value(registry, value)?;
+
+    Ok(DynamicAction {
+        name: C::NAME.to_string(),
+        action: Box::new(value),
+    })
+}
+
+fn control_execute_impl<C: ControlAction>(
+    registry: &ActionRegistry,
+    action: &DynamicAction,
+    context: &mut dyn ExecutionContext,
+) -> Result<(), ActionError> {
+    let value = action
+        .action
+        .downcast_ref::<C>()
+        .ok_or_else(|| ActionError::InvalidType {
+            have: action.name.clone(),
+            expected: C::NAME,
+        })?;
+
+    unsafe { value.execute(registry, context) }
+}
+
+fn to_rust_impl<A: Action>(_: &ActionRegistry, action: &DynamicAction) -> Option<String> {
+    action.action.downcast_ref::<A>()?.to_rust()
+}
+
+fn control_to_rust_impl<C: ControlAction>(
+    registry: &ActionRegistry,
+    action: &DynamicAction,
+) -> Option<String> {
+    action.action.downcast_ref::<C>()?.to_rust(registry)
+}
+
+fn wait_impl<A: Action>(action: &DynamicAction) -> Option<Wait> {
+    action.action.downcast_ref::<A>()?.wait()
+}
+
+fn from_wait_impl<A: Action>(wait: Wait) -> Option<DynamicAction> {
+    A::from_wait(wait).map(|action| DynamicAction {
+        name: A::NAME.to_string(),
+        action: Box::new(action),
+    })
+}
+
+fn motion_rate_impl<A: Action>(action: &DynamicAction) -> Option<MotionRate> {
+    action.action.downcast_ref::<A>()?.motion_rate()
+}
+
+fn blocks_impl<A: Action>(_: &DynamicAction) -> Vec<&ActionBlock> {
+    vec![]
+}
+
+fn control_blocks_impl<C: ControlAction>(action: &DynamicAction) -> Vec<&ActionBlock> {
+    action
+        .action
+        .downcast_ref::<C>()
+        .map(|action| action.blocks())
+        .unwrap_or_default()
+}
+
+fn control_always_waits_impl<C: ControlAction>(
+    registry: &ActionRegistry,
+    action: &DynamicAction,
+) -> bool {
+    action
+        .action
+        .downcast_ref::<C>()
+        .is_some_and(|action| action.always_waits(registry))
+}
+
+fn control_waits_elsewhere_impl<C: ControlAction>(action: &DynamicAction) -> bool {
+    action
+        .action
+        .downcast_ref::<C>()
+        .is_some_and(ControlAction::waits_elsewhere)
+}
+
+#[derive(Error, Debug)]
+pub enum RegistrationError {
+    #[error("'{name}' is already used by action '{owner}'")]
+    Conflict { name: String, owner: String },
+
+    #[error("Action '{name}' is not registered")]
+    NotRegistered { name: String },
+}
+
+/// The name of an action registered in a namespace, like `my_plugin.Attack.set`
+pub fn namespaced(namespace: &str, name: &str) -> String {
+    format!("{namespace}.{name}")
+}
+
 #[repr(C)]
 pub struct RegisteredAction {
-    into_value: fn(&DynamicAction) -> Result<Value, ActionError>,
-    from_value: fn(Value) -> Result<DynamicAction, ActionError>,
-    execute: fn(&DynamicAction, &mut L2CAgentBase) -> Result<(), ActionError>,
+    acmd_name: String,
+    namespace: Option<String>,
+
+    /// Other names that scripts can refer to the action by, such as its name before a rename
+    aliases: Vec<String>,
+
+    is_control: bool,
+    into_value: fn(&ActionRegistry, &DynamicAction) -> Result<Value, ActionError>,
+    from_value: fn(&ActionRegistry, Value) -> Result<DynamicAction, ActionError>,
+    execute:
+        fn(&ActionRegistry, &DynamicAction, &mut dyn ExecutionContext) -> Result<(), ActionError>,
+    blocks: fn(&DynamicAction) -> Vec<&ActionBlock>,
+    always_waits: fn(&ActionRegistry, &DynamicAction) -> bool,
+    waits_elsewhere: fn(&DynamicAction) -> bool,
+    to_rust: fn(&ActionRegistry, &DynamicAction) -> Option<String>,
+    wait: fn(&DynamicAction) -> Option<Wait>,
+    from_wait: fn(Wait) -> Option<DynamicAction>,
+    motion_rate: fn(&DynamicAction) -> Option<MotionRate>,
+    schema: fn(&mut SchemaGenerator) -> Schema,
+    metadata: fn() -> ActionMetadata,
+}
+
+impl RegisteredAction {
+    fn action<A: Action>(namespace: Option<&str>) -> Self {
+        Self {
+            acmd_name: match namespace {
+                Some(namespace) => namespaced(namespace, A::ACMD_NAME),
+                None => A::ACMD_NAME.to_string(),
+            },
+            namespace: namespace.map(str::to_string),
+            aliases: vec![],
+            is_control: false,
+            into_value: into_value_impl::<A>,
+            from_value: from_value_impl::<A>,
+            execute: execute_impl::<A>,
+            blocks: blocks_impl::<A>,
+            always_waits: |_, _| false,
+            waits_elsewhere: |_| false,
+            to_rust: to_rust_impl::<A>,
+            wait: wait_impl::<A>,
+            from_wait: from_wait_impl::<A>,
+            motion_rate: motion_rate_impl::<A>,
+            schema: A::schema,
+            metadata: A::metadata,
+        }
+    }
+
+    fn control<C: ControlAction>(namespace: Option<&str>) -> Self {
+        Self {
+            acmd_name: match namespace {
+                Some(namespace) => namespaced(namespace, C::ACMD_NAME),
+                None => C::ACMD_NAME.to_string(),
+            },
+            namespace: namespace.map(str::to_string),
+            aliases: vec![],
+            is_control: true,
+            into_value: control_into_value_impl::<C>,
+            from_value: control_from_value_impl::<C>,
+            execute: control_execute_impl::<C>,
+            blocks: control_blocks_impl::<C>,
+            always_waits: control_always_waits_impl::<C>,
+            waits_elsewhere: control_waits_elsewhere_impl::<C>,
+            to_rust: control_to_rust_impl::<C>,
+            wait: |_| None,
+            from_wait: |_| None,
+            motion_rate: |_| None,
+            schema: C::schema,
+            metadata: C::metadata,
+        }
+    }
+
+    /// Copies what is needed to describe an action, so that the schema is generated after the
+    /// registry lock is released
+    fn info(&self, name: &str) -> impl FnOnce() -> ActionInfo {
+        let mut info = ActionInfo {
+            name: name.to_string(),
+            acmd_name: self.acmd_name.clone(),
+            namespace: self.namespace.clone(),
+            aliases: self.aliases.clone(),
+            is_control: self.is_control,
+            metadata: ActionMetadata::default(),
+        };
+        let (schema, metadata) = (self.schema, self.metadata);
+
+        move || {
+            let mut generator = schema::generator();
+            let schema = schema(&mut generator);
+            info.metadata = metadata();
+            info.metadata.fill_from_schema(&generator, &schema);
+            info
+        }
+    }
+
+    /// Whether any of the action's functions are in `range`, which means that they were
+    /// registered by the plugin loaded at that address
+    fn is_in(&self, range: &Range<usize>) -> bool {
+        [
+            self.into_value as usize,
+            self.from_value as usize,
+            self.execute as usize,
+            self.blocks as usize,
+            self.to_rust as usize,
+            self.wait as usize,
+            self.from_wait as usize,
+            self.motion_rate as usize,
+            self.schema as usize,
+            self.metadata as usize,
+        ]
+        .iter()
+        .any(|address| range.contains(address))
+    }
+
+    /// Every name that a script can write the action with, starting with the registered `name`
+    fn names<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> {
+        std::iter::once(name)
+            .chain(std::iter::once(self.acmd_name.as_str()))
+            .chain(self.aliases.iter().map(String::as_str))
+    }
+}
+
+/// Finds the registered name of the action that a script name refers to
+fn owner<'a>(reg: &'a BTreeMap<String, RegisteredAction>, name: &str) -> Option<&'a String> {
+    if let Some((registered, _)) = reg.get_key_value(name) {
+        return Some(registered);
+    }
+
+    reg.iter()
+        .find(|(registered, action)| action.names(registered).any(|other| other == name))
+        .map(|(registered, _)| registered)
 }
 
 #[repr(C)]
@@ -111,6 +610,21 @@ pub struct ActionRegistry {
 
     #[cfg(feature = "parking_lot")]
     pub registry: parking_lot::RwLock<BTreeMap<String, RegisteredAction>>,
+
+    #[cfg(not(feature = "parking_lot"))]
+    observers: locks::RwLock<Vec<RegisteredObserver>>,
+
+    #[cfg(feature = "parking_lot")]
+    observers: parking_lot::RwLock<Vec<RegisteredObserver>>,
+}
+
+/// An observer added with [`ActionRegistry::add_observer`]
+#[derive(Clone)]
+struct RegisteredObserver {
+    observer: Arc<dyn ExecutionObserver>,
+    /// Made for the type of the observer by the plugin that added it, like the functions of a
+    /// [`RegisteredAction`], so that its address is in that plugin
+    owner: fn() -> TypeId,
 }
 
 impl ActionRegistry {
@@ -119,6 +633,7 @@ impl ActionRegistry {
         {
             Self {
                 registry: locks::RwLock::new(BTreeMap::new()),
+                observers: locks::RwLock::new(Vec::new()),
             }
         }
 
@@ -126,20 +641,131 @@ impl ActionRegistry {
         {
             Self {
                 registry: parking_lot::RwLock::new(BTreeMap::new()),
+                observers: parking_lot::RwLock::new(Vec::new()),
             }
         }
     }
 
-    pub fn register<A: Action>(&self) -> &Self {
-        self.registry.write().insert(
-            A::NAME.to_string(),
-            RegisteredAction {
-                into_value: into_value_impl::<A>,
-                from_value: from_value_impl::<A>,
-                execute: execute_impl::<A>,
-            },
-        );
-        self
+    /// Adds an action, failing instead of replacing another action if any of its names are taken
+    fn insert(&self, name: String, action: RegisteredAction) -> Result<&Self, RegistrationError> {
+        let mut reg = self.registry.write();
+        for other in action.names(&name) {
+            if let Some(owner) = owner(&reg, other) {
+                return Err(RegistrationError::Conflict {
+                    name: other.to_string(),
+                    owner: owner.clone(),
+                });
+            }
+        }
+
+        reg.insert(name, action);
+        Ok(self)
+    }
+
+    pub fn register<A: Action>(&self) -> Result<&Self, RegistrationError> {
+        self.insert(A::NAME.to_string(), RegisteredAction::action::<A>(None))
+    }
+
+    /// Registers an action under `namespace`, so that both its name and its ACMD name are
+    /// prefixed with it, see [`namespaced`]
+    pub fn register_in<A: Action>(&self, namespace: &str) -> Result<&Self, RegistrationError> {
+        self.insert(
+            namespaced(namespace, A::NAME),
+            RegisteredAction::action::<A>(Some(namespace)),
+        )
+    }
+
+    pub fn register_control<C: ControlAction>(&self) -> Result<&Self, RegistrationError> {
+        self.insert(C::NAME.to_string(), RegisteredAction::control::<C>(None))
+    }
+
+    /// Same as [`ActionRegistry::register_in`], for control actions
+    pub fn register_control_in<C: ControlAction>(
+        &self,
+        namespace: &str,
+    ) -> Result<&Self, RegistrationError> {
+        self.insert(
+            namespaced(namespace, C::NAME),
+            RegisteredAction::control::<C>(Some(namespace)),
+        )
+    }
+
+    /// Removes an action by its registered name.
+    ///
+    /// Scripts that were already loaded keep their copies of the action, but can't run or save
+    /// them anymore.
+    pub fn unregister(&self, name: &str) -> Result<(), RegistrationError> {
+        self.registry
+            .write()
+            .remove(name)
+            .map(|_| ())
+            .ok_or_else(|| RegistrationError::NotRegistered {
+                name: name.to_string(),
+            })
+    }
+
+    /// Removes every action registered in `namespace`, returning how many there were
+    pub fn unregister_namespace(&self, namespace: &str) -> usize {
+        let mut reg = self.registry.write();
+        let len = reg.len();
+        reg.retain(|_, action| action.namespace.as_deref() != Some(namespace));
+        len - reg.len()
+    }
+
+    /// Removes every action whose functions are in `range`, returning how many there were.
+    ///
+    /// This is used when a plugin is unloaded, so that it can register its actions again once it
+    /// is reloaded instead of conflicting with its previous registration.
+    pub fn unregister_range(&self, range: Range<usize>) -> usize {
+        let mut reg = self.registry.write();
+        let len = reg.len();
+        reg.retain(|_, action| !action.is_in(&range));
+        len - reg.len()
+    }
+
+    /// Lets scripts refer to the action registered as `name` by `alias` as well, which keeps
+    /// older scripts loading after an action is renamed.
+    ///
+    /// Loaded actions always use the registered name, so the alias is replaced when a script is
+    /// saved again.
+    pub fn alias(&self, alias: impl Into<String>, name: &str) -> Result<&Self, RegistrationError> {
+        let alias = alias.into();
+        let mut reg = self.registry.write();
+
+        if let Some(owner) = owner(&reg, &alias) {
+            return Err(RegistrationError::Conflict {
+                name: alias,
+                owner: owner.clone(),
+            });
+        }
+
+        let Some(action) = reg.get_mut(name) else {
+            return Err(RegistrationError::NotRegistered {
+                name: name.to_string(),
+            });
+        };
+
+        action.aliases.push(alias);
+        Ok(self)
+    }
+
+    /// Finds the registered name of an action from its name, its ACMD name or one of its aliases
+    pub fn resolve_name(&self, name: &str) -> Option<String> {
+        owner(&self.registry.read(), name).cloned()
+    }
+
+    pub fn acmd_name(&self, name: &str) -> Option<String> {
+        self.registry
+            .read()
+            .get(name)
+            .map(|registered| registered.acmd_name.clone())
+    }
+
+    pub fn is_control(&self, name: &str) -> bool {
+        self.registry
+            .read()
+            .get(name)
+            .is_some_and(|registered| registered.is_control)
     }
 
     pub fn as_action(&self, value: Value) -> Result<DynamicAction, ActionError> {
@@ -154,22 +780,45 @@ impl ActionRegistry {
             }
             _ => return Err(ActionError::InvalidFormat),
         };
-        let reg = self.registry.read();
+        let (registered, from_value) = {
+            let reg = self.registry.read();
+
+            // Aliases are accepted too, but ACMD names are only for the text syntax
+            let action = reg.get_key_value(&name).or_else(|| {
+                reg.iter()
+                    .find(|(_, action)| action.aliases.contains(&name))
+            });
+
+            let Some((registered, action)) = action else {
+                return Err(ActionError::NotRegistered { name });
+            };
 
-        let Some(action) = reg.get(&name) else {
-            return Err(ActionError::NotRegistered { name });
+            (registered.clone(), action.from_value)
         };
 
-        (action.from_value)(value)
+        // The registry lock is released before converting since control actions
+        // need to convert their nested actions through the registry as well
+        let mut action = from_value(self, value).map_err(|error| match error {
+            error @ (ActionError::Nested { .. } | ActionError::Multiple(_)) => error.at_key(name),
+            error => error,
+        })?;
+
+        // Namespaced and aliased actions are created with the action's own name
+        action.name = registered;
+        Ok(action)
     }
 
     pub fn as_value(&self, action: &DynamicAction) -> Result<Value, ActionError> {
-        let reg = self.registry.read();
-        let Some(registered) = reg.get(&action.name) else {
-            return Err(ActionError::NotRegistered { name: action.name.to_string() });
+        let into_value = {
+            let reg = self.registry.read();
+            let Some(registered) = reg.get(&action.name) else {
+                return Err(ActionError::NotRegistered { name: action.name.to_string() });
+            };
+
+            registered.into_value
         };
 
-        let value = (registered.into_value)(action)?;
+        let value = into_value(self, action)?;
 
         match value {
             Value::Null => Ok(Value::String(action.name.to_string())),
@@ -184,13 +833,228 @@ impl ActionRegistry {
     pub fn execute(
         &self,
         action: &DynamicAction,
-        context: &mut L2CAgentBase,
+        context: &mut dyn ExecutionContext,
     ) -> Result<(), ActionError> {
-        let reg = self.registry.read();
-        let Some(registered) = reg.get(&action.name) else {
-            return Err(ActionError::NotRegistered { name: action.name.to_string() });
+        let execute = {
+            let reg = self.registry.read();
+            let Some(registered) = reg.get(&action.name) else {
+                return Err(ActionError::NotRegistered { name: action.name.to_string() });
+            };
+
+            registered.execute
         };
 
-        (registered.execute)(action, context)
+        // Observers are called without holding the lock, so that they can add or remove observers
+        let observers = self.observers.read().clone();
+        for RegisteredObserver { observer, .. } in observers.iter() {
+            observer.on_action_start(action, context);
+        }
+
+        // Not holding the lock here matters, actions can yield the coroutine and
+        // control actions call back into the registry for their children
+        let result = execute(self, action, context);
+
+        for RegisteredObserver { observer, .. } in observers.iter() {
+            observer.on_action_end(action, context, &result);
+        }
+
+        result
+    }
+
+    /// Runs every action of a script, passing the errors of the actions that fail to `on_error`
+    /// instead of stopping at them
+    pub fn execute_script(
+        &self,
+        script: &SmashlineScript,
+        context: &mut dyn ExecutionContext,
+        mut on_error: impl FnMut(&DynamicAction, ActionError),
+    ) {
+        let observers = self.observers.read().clone();
+        for RegisteredObserver { observer, .. } in observers.iter() {
+            observer.on_script_start(script, context);
+        }
+
+        for action in script.actions.iter() {
+            if let Err(error) = self.execute(action, context) {
+                on_error(action, error);
+            }
+        }
+
+        for RegisteredObserver { observer, .. } in observers.iter() {
+            observer.on_script_end(script, context);
+        }
+    }
+
+    /// Adds an observer that sees every script and action run through the registry, see
+    /// [`observer`](crate::observer)
+    pub fn add_observer<O: ExecutionObserver + 'static>(&self, observer: Arc<O>) {
+        self.observers.write().push(RegisteredObserver {
+            observer,
+            owner: TypeId::of::<O>,
+        });
+    }
+
+    /// Removes an observer that was added with [`ActionRegistry::add_observer`], returning whether
+    /// it was found
+    pub fn remove_observer(&self, observer: &Arc<dyn ExecutionObserver>) -> bool {
+        let mut observers = self.observers.write();
+        let count = observers.len();
+        observers.retain(|other| !Arc::ptr_eq(&other.observer, observer));
+        observers.len() != count
+    }
+
+    /// Removes every observer added by the plugin loaded at `range`, returning how many there
+    /// were. This is used when the plugin is unloaded, see [`ActionRegistry::unregister_range`].
+    pub fn remove_observers_range(&self, range: Range<usize>) -> usize {
+        let mut observers = self.observers.write();
+        let count = observers.len();
+        observers.retain(|observer| !range.contains(&(observer.owner as usize)));
+        count - observers.len()
+    }
+
+    /// The Rust equivalent of an action, or `None` if it doesn't describe one
+    pub fn to_rust(&self, action: &DynamicAction) -> Option<String> {
+        let to_rust = self.registry.read().get(&action.name)?.to_rust;
+        to_rust(self, action)
+    }
+
+    /// How long an action makes the script wait, `None` for anything other than a wait
+    pub fn wait(&self, action: &DynamicAction) -> Option<Wait> {
+        let wait = self.registry.read().get(&action.name)?.wait;
+        wait(action)
+    }
+
+    /// Whether running an action waits for at least one frame on every path through it.
+    ///
+    /// Only a [`Wait::For`] of a positive number of frames counts as a wait, since a
+    /// [`Wait::Until`] does nothing once its frame has passed. Control actions decide for
+    /// themselves with [`ControlAction::always_waits`].
+    pub fn always_waits(&self, action: &DynamicAction) -> bool {
+        if let Some(wait) = self.wait(action) {
+            return matches!(wait, Wait::For(frames) if frames > 0.0);
+        }
+
+        let always_waits = match self.registry.read().get(&action.name) {
+            Some(registered) => registered.always_waits,
+            None => return false,
+        };
+        always_waits(self, action)
+    }
+
+    /// Whether an action can wait outside of its nested actions, see
+    /// [`ControlAction::waits_elsewhere`]
+    pub fn waits_elsewhere(&self, action: &DynamicAction) -> bool {
+        let waits_elsewhere = match self.registry.read().get(&action.name) {
+            Some(registered) => registered.waits_elsewhere,
+            None => return false,
+        };
+        waits_elsewhere(action)
+    }
+
+    /// How an action changes the motion rate, `None` for anything that doesn't
+    pub fn motion_rate(&self, action: &DynamicAction) -> Option<MotionRate> {
+        let motion_rate = self.registry.read().get(&action.name)?.motion_rate;
+        motion_rate(action)
+    }
+
+    /// Creates an action which waits like `wait`, from the first registered action that can
+    pub fn wait_action(&self, wait: Wait) -> Option<DynamicAction> {
+        self.registry.read().iter().find_map(|(name, registered)| {
+            (registered.from_wait)(wait).map(|mut action| {
+                action.name = name.clone();
+                action
+            })
+        })
+    }
+
+    /// Calls `f` on every action in `actions`, including the ones nested inside of control actions
+    pub fn visit<'a>(&self, actions: &'a [DynamicAction], f: &mut dyn FnMut(&'a DynamicAction)) {
+        for action in actions {
+            f(action);
+
+            let blocks = {
+                let reg = self.registry.read();
+                match reg.get(&action.name) {
+                    Some(registered) => registered.blocks,
+                    None => continue,
+                }
+            };
+
+            for block in blocks(action) {
+                self.visit(&block.0, f);
+            }
+        }
+    }
+
+    /// Adds the [`schema::ACTION_DEFINITION`] definition, the union of every registered action
+    pub fn define_actions(&self, generator: &mut SchemaGenerator) {
+        let schemas: Vec<_> = self
+            .registry
+            .read()
+            .iter()
+            .map(|(name, registered)| (name.clone(), registered.schema))
+            .collect();
+
+        let actions = schemas
+            .into_iter()
+            .map(|(name, schema)| {
+                let value = schema(generator);
+                schema::wrap_action(generator, &name, value)
+            })
+            .collect();
+
+        generator.definitions_mut().insert(
+            schema::ACTION_DEFINITION.to_string(),
+            SchemaObject {
+                subschemas: Some(Box::new(SubschemaValidation {
+                    any_of: Some(actions),
+                    ..Default::default()
+                })),
+                ..Default::default()
+            }
+            .into(),
+        );
+    }
+
+    /// Every registered action along with its metadata, sorted by name
+    pub fn actions(&self) -> Vec<ActionInfo> {
+        let registered: Vec<_> = self
+            .registry
+            .read()
+            .iter()
+            .map(|(name, registered)| registered.info(name))
+            .collect();
+
+        registered.into_iter().map(|info| info()).collect()
+    }
+
+    /// The metadata of a single action, from any of the names that scripts can use for it
+    pub fn action_info(&self, name: &str) -> Option<ActionInfo> {
+        let info = {
+            let reg = self.registry.read();
+            let name = owner(&reg, name)?;
+            reg.get(name)?.info(name)
+        };
+
+        Some(info())
+    }
+
+    /// Generates the JSON schema for a single registered action, as it is written in a script
+    pub fn action_schema(&self, name: &str) -> Option<RootSchema> {
+        let schema = self.registry.read().get(name).map(|registered| registered.schema)?;
+
+        let mut generator = schema::generator();
+
+        // Control actions refer back to the full set of actions for their nested blocks
+        self.define_actions(&mut generator);
+
+        let value = schema(&mut generator);
+        let action = schema::wrap_action(&generator, name, value);
+
+        Some(RootSchema {
+            meta_schema: generator.settings().meta_schema.clone(),
+            schema: action.into_object(),
+            definitions: generator.take_definitions(),
+        })
     }
 }
@@ -1,10 +1,21 @@
+use std::collections::BTreeMap;
+
 use hash40::Hash40;
+use schemars::{
+    schema::{InstanceType, ObjectValidation, RootSchema, Schema, SchemaObject},
+    JsonSchema,
+};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::action::{ActionError, ActionRegistry};
+use crate::{
+    action::{ActionError, ActionRegistry},
+    patch::PatchOperation,
+    schema,
+    template::TemplateParameter,
+};
 
-#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Deserialize, Serialize, JsonSchema, Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Category {
     Game,
     Effect,
@@ -12,31 +23,247 @@ pub enum Category {
     Expression,
 }
 
+/// The version string that scripts are written with
+pub const LATEST_VERSION: &str = "0.2.0";
+
+/// The newest script format, older formats are upgraded to it when they are loaded
+pub type LatestSmashlineScript = SmashlineScriptV1;
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(tag = "version")]
 pub enum VersionedSmashlineScript {
     #[serde(alias = "0.1.0")]
     #[serde(rename = "0.1.0")]
     V0(SmashlineScriptV0),
+
+    #[serde(rename = "0.2.0")]
+    V1(SmashlineScriptV1),
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SmashlineScriptV0 {
+    #[serde(with = "crate::labels::hash")]
+    pub agent: Hash40,
+    pub category: Category,
+    #[serde(with = "crate::labels::hash")]
+    pub replace: Hash40,
+    pub actions: Vec<Value>,
+}
+
+/// Information about a script which isn't used when running it
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Default, Clone, PartialEq, Eq)]
+pub struct ScriptMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl ScriptMetadata {
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none() && self.author.is_none() && self.description.is_none()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SmashlineScriptV1 {
+    #[serde(with = "crate::labels::hash")]
     pub agent: Hash40,
     pub category: Category,
+    #[serde(with = "crate::labels::hash")]
     pub replace: Hash40,
+
+    /// The costume slots the script is installed for. Without any, it is installed for every
+    /// costume that no other script of the agent targets. This only picks between script files,
+    /// the scripts and callbacks that plugins install for an agent don't depend on it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub costumes: Vec<usize>,
+
+    #[serde(default, skip_serializing_if = "ScriptMetadata::is_empty")]
+    pub metadata: ScriptMetadata,
+
     pub actions: Vec<Value>,
 }
 
+/// The newest patch format, see [`patch`](crate::patch)
+pub type LatestScriptPatch = ScriptPatchV1;
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "version")]
+pub enum VersionedScriptPatch {
+    #[serde(rename = "0.2.0")]
+    V1(ScriptPatchV1),
+}
+
+impl VersionedScriptPatch {
+    pub fn into_latest(self) -> LatestScriptPatch {
+        match self {
+            Self::V1(v1) => v1,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ScriptPatchV1 {
+    #[serde(with = "crate::labels::hash")]
+    pub agent: Hash40,
+    pub category: Category,
+
+    /// The `replace` hash of the scripts this patch applies to
+    #[serde(with = "crate::labels::hash")]
+    pub patch: Hash40,
+
+    #[serde(default, skip_serializing_if = "ScriptMetadata::is_empty")]
+    pub metadata: ScriptMetadata,
+
+    pub operations: Vec<PatchOperation>,
+}
+
+/// The newest template format, see [`template`](crate::template)
+pub type LatestScriptTemplate = ScriptTemplateV1;
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "version")]
+pub enum VersionedScriptTemplate {
+    #[serde(rename = "0.2.0")]
+    V1(ScriptTemplateV1),
+}
+
+impl VersionedScriptTemplate {
+    pub fn into_latest(self) -> LatestScriptTemplate {
+        match self {
+            Self::V1(v1) => v1,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ScriptTemplateV1 {
+    /// The name that scripts instantiate the template with
+    pub template: String,
+
+    #[serde(default, skip_serializing_if = "ScriptMetadata::is_empty")]
+    pub metadata: ScriptMetadata,
+
+    #[serde(default)]
+    pub parameters: BTreeMap<String, TemplateParameter>,
+
+    pub actions: Vec<Value>,
+}
+
+/// The newest bundle format, which holds many scripts of one agent in a single file
+pub type LatestScriptBundle = ScriptBundleV1;
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "version")]
+pub enum VersionedScriptBundle {
+    #[serde(rename = "0.2.0")]
+    V1(ScriptBundleV1),
+}
+
+impl VersionedScriptBundle {
+    pub fn into_latest(self) -> LatestScriptBundle {
+        match self {
+            Self::V1(v1) => v1,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ScriptBundleV1 {
+    #[serde(with = "crate::labels::hash")]
+    pub agent: Hash40,
+
+    #[serde(default, skip_serializing_if = "ScriptMetadata::is_empty")]
+    pub metadata: ScriptMetadata,
+
+    /// The scripts of the bundle by the name of their entry, which is used to reload one of them
+    pub scripts: BTreeMap<String, BundleEntryV1>,
+}
+
+/// A script of a bundle, which shares the agent of the bundle
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BundleEntryV1 {
+    pub category: Category,
+    #[serde(with = "crate::labels::hash")]
+    pub replace: Hash40,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub costumes: Vec<usize>,
+
+    #[serde(default, skip_serializing_if = "ScriptMetadata::is_empty")]
+    pub metadata: ScriptMetadata,
+
+    pub actions: Vec<Value>,
+}
+
+impl ScriptBundleV1 {
+    /// Splits the bundle into its scripts, by entry name
+    pub fn into_scripts(self) -> impl Iterator<Item = (String, LatestSmashlineScript)> {
+        let agent = self.agent;
+        self.scripts.into_iter().map(move |(name, entry)| {
+            let script = SmashlineScriptV1 {
+                agent,
+                category: entry.category,
+                replace: entry.replace,
+                costumes: entry.costumes,
+                metadata: entry.metadata,
+                actions: entry.actions,
+            };
+            (name, script)
+        })
+    }
+}
+
+impl From<SmashlineScriptV0> for SmashlineScriptV1 {
+    fn from(v0: SmashlineScriptV0) -> Self {
+        Self {
+            agent: v0.agent,
+            category: v0.category,
+            replace: v0.replace,
+            costumes: vec![],
+            metadata: ScriptMetadata::default(),
+            actions: v0.actions,
+        }
+    }
+}
+
 impl VersionedSmashlineScript {
+    /// Upgrades the script through every format version until it is the latest one
+    pub fn into_latest(self) -> LatestSmashlineScript {
+        match self {
+            Self::V0(v0) => SmashlineScriptV1::from(v0),
+            Self::V1(v1) => v1,
+        }
+    }
+
+    pub fn is_latest(&self) -> bool {
+        matches!(self, Self::V1(_))
+    }
+
+    /// Rewrites a script file in the latest format.
+    ///
+    /// The actions are carried over as they are, so no actions need to be registered.
+    pub fn migrate_json(json: impl AsRef<str>) -> Result<String, ActionError> {
+        let versioned: Self = serde_json::from_str(json.as_ref()).map_err(ActionError::ParseError)?;
+        let latest = Self::V1(versioned.into_latest());
+        serde_json::to_string_pretty(&latest).map_err(ActionError::SerializeError)
+    }
+
     pub fn from_latest(
         registry: &ActionRegistry,
         latest: &crate::SmashlineScript,
     ) -> Result<Self, ActionError> {
-        let v0 = SmashlineScriptV0 {
+        let v1 = SmashlineScriptV1 {
             agent: latest.agent,
             category: latest.category,
             replace: latest.replace,
+            costumes: latest.costumes.clone(),
+            metadata: latest.metadata.clone(),
             actions: latest
                 .actions
                 .iter()
@@ -44,6 +271,52 @@ impl VersionedSmashlineScript {
                 .collect::<Result<Vec<_>, _>>()?,
         };
 
-        Ok(VersionedSmashlineScript::V0(v0))
+        Ok(VersionedSmashlineScript::V1(v1))
+    }
+
+    /// Generates the JSON schema of a script file in the latest format, using the actions
+    /// currently registered
+    pub fn schema(registry: &ActionRegistry) -> RootSchema {
+        let mut generator = schema::generator();
+        registry.define_actions(&mut generator);
+
+        let version: Schema = SchemaObject {
+            const_value: Some(Value::String(LATEST_VERSION.to_string())),
+            ..Default::default()
+        }
+        .into();
+
+        let mut object = ObjectValidation::default();
+        object.properties.insert("version".to_string(), version);
+        object
+            .properties
+            .insert("agent".to_string(), generator.subschema_for::<String>());
+        object
+            .properties
+            .insert("category".to_string(), generator.subschema_for::<Category>());
+        object
+            .properties
+            .insert("replace".to_string(), generator.subschema_for::<String>());
+        object
+            .properties
+            .insert("actions".to_string(), schema::action_list(&mut generator));
+        object.required = object.properties.keys().cloned().collect();
+
+        object
+            .properties
+            .insert("costumes".to_string(), generator.subschema_for::<Vec<usize>>());
+        object
+            .properties
+            .insert("metadata".to_string(), generator.subschema_for::<ScriptMetadata>());
+
+        RootSchema {
+            meta_schema: generator.settings().meta_schema.clone(),
+            schema: SchemaObject {
+                instance_type: Some(InstanceType::Object.into()),
+                object: Some(Box::new(object)),
+                ..Default::default()
+            },
+            definitions: generator.take_definitions(),
+        }
     }
 }
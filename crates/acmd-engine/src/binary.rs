@@ -0,0 +1,362 @@
+//! A compact binary encoding of [`VersionedSmashlineScript`](crate::asset::VersionedSmashlineScript)
+//!
+//! The encoding mirrors the JSON data model so that converting between the two is lossless, but
+//! every string (action names, constants, hashes) is stored once in a string table and numbers are
+//! stored as varints or raw floats instead of text.
+//!
+//! Layout:
+//! - `MAGIC` followed by the `FORMAT_VERSION` byte
+//! - the string table, a varint count followed by length-prefixed UTF-8 strings
+//! - the root value
+
+use std::collections::HashMap;
+
+use serde_json::{Map, Number, Value};
+use thiserror::Error;
+
+pub const MAGIC: [u8; 4] = *b"ACMB";
+pub const FORMAT_VERSION: u8 = 1;
+
+const TAG_NULL: u8 = 0;
+const TAG_FALSE: u8 = 1;
+const TAG_TRUE: u8 = 2;
+const TAG_UINT: u8 = 3;
+const TAG_NEG_INT: u8 = 4;
+const TAG_F32: u8 = 5;
+const TAG_F64: u8 = 6;
+const TAG_STRING: u8 = 7;
+const TAG_ARRAY: u8 = 8;
+const TAG_OBJECT: u8 = 9;
+
+/// How deeply arrays and objects can be nested, the same limit `serde_json` has for JSON files
+const MAX_DEPTH: usize = 128;
+
+#[derive(Error, Debug)]
+pub enum DecodeError {
+    #[error("File is not a binary smashline script")]
+    InvalidMagic,
+
+    #[error("Binary format version {0} is not supported (expected {FORMAT_VERSION})")]
+    UnsupportedVersion(u8),
+
+    #[error("Unexpected end of data")]
+    UnexpectedEof,
+
+    #[error("Invalid value tag {0:#x}")]
+    InvalidTag(u8),
+
+    #[error("String index {0} is out of bounds")]
+    InvalidString(usize),
+
+    #[error("String table contains invalid UTF-8")]
+    InvalidUtf8,
+
+    #[error("Float value is not representable in JSON")]
+    InvalidNumber,
+
+    #[error("Values are nested more than {MAX_DEPTH} levels deep")]
+    TooDeep,
+
+    #[error("{0} bytes of trailing data")]
+    TrailingData(usize),
+}
+
+#[derive(Default)]
+struct Encoder {
+    strings: Vec<String>,
+    indices: HashMap<String, usize>,
+    body: Vec<u8>,
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+impl Encoder {
+    fn string(&mut self, string: &str) {
+        let index = match self.indices.get(string) {
+            Some(index) => *index,
+            None => {
+                let index = self.strings.len();
+                self.strings.push(string.to_string());
+                self.indices.insert(string.to_string(), index);
+                index
+            }
+        };
+
+        write_varint(&mut self.body, index as u64);
+    }
+
+    fn value(&mut self, value: &Value) {
+        match value {
+            Value::Null => self.body.push(TAG_NULL),
+            Value::Bool(false) => self.body.push(TAG_FALSE),
+            Value::Bool(true) => self.body.push(TAG_TRUE),
+            Value::Number(number) => {
+                if let Some(uint) = number.as_u64() {
+                    self.body.push(TAG_UINT);
+                    write_varint(&mut self.body, uint);
+                } else if let Some(int) = number.as_i64() {
+                    // Only negative values end up here, store the magnitude minus one
+                    self.body.push(TAG_NEG_INT);
+                    write_varint(&mut self.body, !(int as u64));
+                } else {
+                    let float = number.as_f64().unwrap_or_default();
+                    if (float as f32) as f64 == float {
+                        self.body.push(TAG_F32);
+                        self.body.extend_from_slice(&(float as f32).to_le_bytes());
+                    } else {
+                        self.body.push(TAG_F64);
+                        self.body.extend_from_slice(&float.to_le_bytes());
+                    }
+                }
+            }
+            Value::String(string) => {
+                self.body.push(TAG_STRING);
+                self.string(string);
+            }
+            Value::Array(array) => {
+                self.body.push(TAG_ARRAY);
+                write_varint(&mut self.body, array.len() as u64);
+                for value in array {
+                    self.value(value);
+                }
+            }
+            Value::Object(object) => {
+                self.body.push(TAG_OBJECT);
+                write_varint(&mut self.body, object.len() as u64);
+                for (key, value) in object {
+                    self.string(key);
+                    self.value(value);
+                }
+            }
+        }
+    }
+}
+
+/// Encodes a JSON value into the binary format
+pub fn encode(value: &Value) -> Vec<u8> {
+    let mut encoder = Encoder::default();
+    encoder.value(value);
+
+    let mut out = Vec::with_capacity(encoder.body.len() + 64);
+    out.extend_from_slice(&MAGIC);
+    out.push(FORMAT_VERSION);
+    write_varint(&mut out, encoder.strings.len() as u64);
+    for string in encoder.strings.iter() {
+        write_varint(&mut out, string.len() as u64);
+        out.extend_from_slice(string.as_bytes());
+    }
+    out.extend_from_slice(&encoder.body);
+    out
+}
+
+struct Decoder<'a> {
+    data: &'a [u8],
+    strings: Vec<String>,
+    depth: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn bytes(&mut self, count: usize) -> Result<&'a [u8], DecodeError> {
+        if self.data.len() < count {
+            return Err(DecodeError::UnexpectedEof);
+        }
+
+        let (bytes, rest) = self.data.split_at(count);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, DecodeError> {
+        self.bytes(1).map(|bytes| bytes[0])
+    }
+
+    fn varint(&mut self) -> Result<u64, DecodeError> {
+        let mut value = 0u64;
+        let mut shift = 0;
+        loop {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7F) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+
+            shift += 7;
+            if shift >= 64 {
+                return Err(DecodeError::InvalidNumber);
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, DecodeError> {
+        let index = self.varint()? as usize;
+        self.strings
+            .get(index)
+            .cloned()
+            .ok_or(DecodeError::InvalidString(index))
+    }
+
+    fn float(float: f64) -> Result<Value, DecodeError> {
+        Number::from_f64(float)
+            .map(Value::Number)
+            .ok_or(DecodeError::InvalidNumber)
+    }
+
+    fn value(&mut self) -> Result<Value, DecodeError> {
+        match self.byte()? {
+            tag @ (TAG_ARRAY | TAG_OBJECT) => {
+                if self.depth == MAX_DEPTH {
+                    return Err(DecodeError::TooDeep);
+                }
+
+                self.depth += 1;
+                let value = self.container(tag);
+                self.depth -= 1;
+                value
+            }
+            TAG_NULL => Ok(Value::Null),
+            TAG_FALSE => Ok(Value::Bool(false)),
+            TAG_TRUE => Ok(Value::Bool(true)),
+            TAG_UINT => Ok(Value::Number(self.varint()?.into())),
+            TAG_NEG_INT => Ok(Value::Number((!self.varint()? as i64).into())),
+            TAG_F32 => {
+                let bytes = self.bytes(4)?.try_into().unwrap();
+                Self::float(f32::from_le_bytes(bytes) as f64)
+            }
+            TAG_F64 => {
+                let bytes = self.bytes(8)?.try_into().unwrap();
+                Self::float(f64::from_le_bytes(bytes))
+            }
+            TAG_STRING => self.string().map(Value::String),
+            tag => Err(DecodeError::InvalidTag(tag)),
+        }
+    }
+
+    fn container(&mut self, tag: u8) -> Result<Value, DecodeError> {
+        match tag {
+            TAG_ARRAY => {
+                let len = self.varint()? as usize;
+                let mut array = Vec::with_capacity(len.min(self.data.len()));
+                for _ in 0..len {
+                    array.push(self.value()?);
+                }
+                Ok(Value::Array(array))
+            }
+            TAG_OBJECT => {
+                let len = self.varint()? as usize;
+                let mut object = Map::new();
+                for _ in 0..len {
+                    let key = self.string()?;
+                    object.insert(key, self.value()?);
+                }
+                Ok(Value::Object(object))
+            }
+            tag => Err(DecodeError::InvalidTag(tag)),
+        }
+    }
+}
+
+/// Returns whether the data starts with the binary format's magic
+pub fn is_binary(data: &[u8]) -> bool {
+    data.starts_with(&MAGIC)
+}
+
+/// Decodes binary data back into the JSON value it was encoded from
+pub fn decode(data: &[u8]) -> Result<Value, DecodeError> {
+    if !is_binary(data) {
+        return Err(DecodeError::InvalidMagic);
+    }
+
+    let mut decoder = Decoder {
+        data: &data[MAGIC.len()..],
+        strings: vec![],
+        depth: 0,
+    };
+
+    let version = decoder.byte()?;
+    if version != FORMAT_VERSION {
+        return Err(DecodeError::UnsupportedVersion(version));
+    }
+
+    let count = decoder.varint()? as usize;
+    for _ in 0..count {
+        let len = decoder.varint()? as usize;
+        let bytes = decoder.bytes(len)?;
+        let string = std::str::from_utf8(bytes).map_err(|_| DecodeError::InvalidUtf8)?;
+        decoder.strings.push(string.to_string());
+    }
+
+    let value = decoder.value()?;
+    if !decoder.data.is_empty() {
+        return Err(DecodeError::TrailingData(decoder.data.len()));
+    }
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let value = json!({
+            "version": "0.2.0",
+            "actions": [
+                null, true, false, 0, 300, u64::MAX, -1, i64::MIN, 1.5, 0.1, -2.25,
+                "WaitUntil", "WaitUntil", [], {}, [[{ "nested": [1] }]]
+            ]
+        });
+
+        assert_eq!(decode(&encode(&value)).unwrap(), value);
+    }
+
+    #[test]
+    fn strings_once() {
+        let data = encode(&json!(["Attack.set", "Attack.set", { "Attack.set": "Attack.set" }]));
+        let count = data
+            .windows("Attack.set".len())
+            .filter(|window| *window == b"Attack.set")
+            .count();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn invalid_data() {
+        let data = encode(&json!({ "actions": [1.5, "frame"] }));
+
+        assert!(matches!(decode(b"{}"), Err(DecodeError::InvalidMagic)));
+        assert!(matches!(
+            decode(&[&MAGIC[..], &[FORMAT_VERSION + 1]].concat()),
+            Err(DecodeError::UnsupportedVersion(_))
+        ));
+        assert!(matches!(
+            decode(&data[..data.len() - 1]),
+            Err(DecodeError::UnexpectedEof)
+        ));
+        assert!(matches!(
+            decode(&[&data[..], &[0]].concat()),
+            Err(DecodeError::TrailingData(1))
+        ));
+
+        let mut nested = json!(null);
+        for _ in 0..=MAX_DEPTH {
+            nested = json!([nested]);
+        }
+        assert!(matches!(
+            decode(&encode(&nested)),
+            Err(DecodeError::TooDeep)
+        ));
+    }
+}
@@ -0,0 +1,248 @@
+use std::{
+    fmt::Display,
+    sync::{Arc, Weak},
+};
+
+use hash40::Hash40;
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::{
+    action::{ActionError, ActionRegistry, ControlAction, DynamicAction},
+    context::ExecutionContext,
+    RwLock, ScriptHandle, SmashlineScript,
+};
+
+#[derive(Error, Debug)]
+pub enum LinkError {
+    #[error("'{file}' calls '{target}', which is not loaded for this agent")]
+    MissingScript { file: String, target: String },
+
+    #[error("'{file}' calls '{target}', which leads back into '{file}'")]
+    Cycle { file: String, target: String },
+}
+
+/// The script that a [`Call`] runs, either by the file it was loaded from or by the script it replaces
+#[derive(Deserialize, Serialize, JsonSchema, Debug, Clone, PartialEq, Eq)]
+pub enum ScriptRef {
+    File(String),
+    Script(
+        #[serde(with = "crate::labels::hash")]
+        #[schemars(with = "String")]
+        Hash40,
+    ),
+}
+
+impl Display for ScriptRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::File(name) => f.write_str(name),
+            Self::Script(hash) => f.write_str(&crate::labels::to_string(*hash)),
+        }
+    }
+}
+
+/// Runs every action of another loaded script in place.
+///
+/// Calls are linked once the scripts of an agent are loaded, see [`link_scripts`]. Since the link
+/// points at the [`ScriptHandle`] instead of its contents, reloading the target is picked up.
+pub struct Call {
+    pub target: ScriptRef,
+    link: RwLock<Option<Weak<RwLock<Arc<SmashlineScript>>>>>,
+}
+
+impl Call {
+    pub fn new(target: ScriptRef) -> Self {
+        Self {
+            target,
+            link: RwLock::new(None),
+        }
+    }
+
+    pub fn is_linked(&self) -> bool {
+        self.link
+            .read()
+            .as_ref()
+            .is_some_and(|link| link.strong_count() > 0)
+    }
+
+    fn set_link(&self, link: Option<&ScriptHandle>) {
+        *self.link.write() = link.map(Arc::downgrade);
+    }
+}
+
+impl ControlAction for Call {
+    const NAME: &'static str = "Call";
+
+    fn from_value(_: &ActionRegistry, value: Value) -> Result<Self, ActionError> {
+        serde_json::from_value(value)
+            .map(Self::new)
+            .map_err(|error| ActionError::FromValueError {
+                name: Self::NAME,
+                error,
+            })
+    }
+
+    fn into_value(&self, _: &ActionRegistry) -> Result<Value, ActionError> {
+        serde_json::to_value(&self.target).map_err(|error| ActionError::IntoValueError {
+            name: Self::NAME,
+            error,
+        })
+    }
+
+    unsafe fn execute(
+        &self,
+        registry: &ActionRegistry,
+        context: &mut dyn ExecutionContext,
+    ) -> Result<(), ActionError> {
+        let Some(handle) = self.link.read().as_ref().and_then(Weak::upgrade) else {
+            return Err(ActionError::UnlinkedCall {
+                target: self.target.to_string(),
+            });
+        };
+
+        let script = handle.read().clone();
+        for action in script.actions.iter() {
+            registry.execute(action, context)?;
+        }
+
+        Ok(())
+    }
+
+    /// The called script isn't followed, since it is only linked once every script of the agent
+    /// is loaded
+    fn waits_elsewhere(&self) -> bool {
+        true
+    }
+
+    fn schema(generator: &mut SchemaGenerator) -> Schema {
+        generator.subschema_for::<ScriptRef>()
+    }
+}
+
+fn collect_calls<'a>(registry: &ActionRegistry, actions: &'a [DynamicAction]) -> Vec<&'a Call> {
+    let mut calls = vec![];
+    // Matched by type rather than by name, since `Call` can be registered in a namespace
+    registry.visit(actions, &mut |action| {
+        if let Some(call) = action.action.downcast_ref::<Call>() {
+            calls.push(call);
+        }
+    });
+    calls
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Visit {
+    New,
+    InProgress,
+    Done,
+}
+
+struct Linker<'a> {
+    names: Vec<&'a str>,
+    edges: Vec<Vec<(&'a Call, usize)>>,
+    state: Vec<Visit>,
+    errors: Vec<LinkError>,
+}
+
+impl<'a> Linker<'a> {
+    fn visit(&mut self, node: usize) {
+        self.state[node] = Visit::InProgress;
+
+        for edge in 0..self.edges[node].len() {
+            let (call, target) = self.edges[node][edge];
+            match self.state[target] {
+                Visit::New => self.visit(target),
+                Visit::InProgress => {
+                    call.set_link(None);
+                    self.errors.push(LinkError::Cycle {
+                        file: self.names[node].to_string(),
+                        target: call.target.to_string(),
+                    });
+                }
+                Visit::Done => {}
+            }
+        }
+
+        self.state[node] = Visit::Done;
+    }
+}
+
+/// The script replacing `hash` for a call from a script for `costumes`. A script for the same
+/// costumes is picked over one for every costume, so that costume scripts call each other.
+fn find_script(
+    contents: &[Arc<SmashlineScript>],
+    hash: Hash40,
+    costumes: &[usize],
+) -> Option<usize> {
+    let replacing = || {
+        contents
+            .iter()
+            .enumerate()
+            .filter(move |(_, script)| script.replace == hash)
+    };
+
+    replacing()
+        .find(|(_, script)| script.costumes == costumes)
+        .or_else(|| replacing().find(|(_, script)| script.costumes.is_empty()))
+        .or_else(|| replacing().next())
+        .map(|(index, _)| index)
+}
+
+/// Links every [`Call`] in a set of scripts loaded for the same agent.
+///
+/// Calls to scripts that aren't part of the set, or calls which would recurse forever, are left
+/// unlinked and reported. This should be run again whenever one of the scripts gets reloaded.
+pub fn link_scripts<'a>(
+    registry: &ActionRegistry,
+    scripts: impl IntoIterator<Item = (&'a str, &'a ScriptHandle)>,
+) -> Vec<LinkError> {
+    let (names, handles): (Vec<&str>, Vec<&ScriptHandle>) = scripts.into_iter().unzip();
+    let contents: Vec<Arc<SmashlineScript>> =
+        handles.iter().map(|handle| handle.read().clone()).collect();
+
+    let mut errors = vec![];
+    let mut edges = vec![];
+
+    for (name, script) in names.iter().zip(contents.iter()) {
+        let mut node_edges = vec![];
+        for call in collect_calls(registry, &script.actions) {
+            let target = match &call.target {
+                ScriptRef::File(file) => names.iter().position(|name| name == file),
+                ScriptRef::Script(hash) => find_script(&contents, *hash, &script.costumes),
+            };
+
+            match target {
+                Some(target) => {
+                    call.set_link(Some(handles[target]));
+                    node_edges.push((call, target));
+                }
+                None => {
+                    call.set_link(None);
+                    errors.push(LinkError::MissingScript {
+                        file: name.to_string(),
+                        target: call.target.to_string(),
+                    });
+                }
+            }
+        }
+        edges.push(node_edges);
+    }
+
+    let mut linker = Linker {
+        state: vec![Visit::New; names.len()],
+        names,
+        edges,
+        errors,
+    };
+
+    for node in 0..linker.names.len() {
+        if linker.state[node] == Visit::New {
+            linker.visit(node);
+        }
+    }
+
+    linker.errors
+}
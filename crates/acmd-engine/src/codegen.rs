@@ -0,0 +1,176 @@
+//! Generating Rust source from a loaded script, to move a finished script into a compiled plugin
+//!
+//! Each action describes itself through [`Action::to_rust`](crate::action::Action::to_rust), with
+//! the agent bound as `agent` and [`PRELUDE`] in scope. Actions without a Rust equivalent are kept
+//! as a comment holding their JSON value so nothing is silently dropped.
+
+use std::fmt::Write;
+
+use hash40::Hash40;
+
+use crate::{
+    action::{ActionBlock, ActionRegistry, DynamicAction},
+    asset::Category,
+    labels, SmashlineScript,
+};
+
+/// The imports that generated scripts are written against
+pub const PRELUDE: &str = "\
+use smash::app::{lua_bind::*, sv_animcmd::*, *};
+use smash::lib::lua_const::*;
+use smash::lua2cpp::*;
+use smash::phx::*;
+use smash_script::*;
+";
+
+/// Formats a float so that it is always a float literal
+pub fn float(value: f32) -> String {
+    if value.is_nan() {
+        "f32::NAN".to_string()
+    } else if value.is_infinite() {
+        format!("{}f32::INFINITY", if value < 0.0 { "-" } else { "" })
+    } else {
+        format!("{value:?}")
+    }
+}
+
+/// Formats a hash as a `Hash40` expression, using its label when it is known
+pub fn hash(hash: Hash40) -> String {
+    match labels::label_of(hash) {
+        Some(label) => format!("Hash40::new({label:?})"),
+        None => format!("Hash40::new_raw({})", labels::to_string(hash)),
+    }
+}
+
+fn indent(out: &mut String, code: &str) {
+    for line in code.lines() {
+        if line.is_empty() {
+            out.push('\n');
+        } else {
+            let _ = writeln!(out, "    {line}");
+        }
+    }
+}
+
+/// The Rust statements for a list of actions
+pub fn actions(registry: &ActionRegistry, actions: &[DynamicAction]) -> String {
+    let mut out = String::new();
+    for action in actions {
+        match registry.to_rust(action) {
+            Some(code) => {
+                out.push_str(&code);
+                if !code.ends_with('\n') {
+                    out.push('\n');
+                }
+            }
+            None => {
+                let value = registry
+                    .as_value(action)
+                    .map(|value| value.to_string())
+                    .unwrap_or_else(|_| action.name.clone());
+                let _ = writeln!(out, "// '{}' has no Rust equivalent: {value}", action.name);
+            }
+        }
+    }
+    out
+}
+
+/// The Rust statements for a nested block, indented one level for the body of an `if` or a loop
+pub fn block(registry: &ActionRegistry, block: &ActionBlock) -> String {
+    let mut out = String::new();
+    indent(&mut out, &actions(registry, &block.0));
+    out
+}
+
+fn attribute_hash(hash: Hash40) -> String {
+    match labels::label_of(hash) {
+        Some(label) => format!("{label:?}"),
+        None => format!("{}_u64", labels::to_string(hash)),
+    }
+}
+
+fn function_name(script: &SmashlineScript) -> String {
+    let name = format!(
+        "{}_{}",
+        labels::to_string(script.agent),
+        labels::to_string(script.replace)
+    );
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("script_{name}")
+    } else {
+        name
+    }
+}
+
+/// Generates a `#[smashline::acmd]` function equivalent to the script
+pub fn script(registry: &ActionRegistry, script: &SmashlineScript) -> String {
+    let mut out = String::new();
+
+    let metadata = &script.metadata;
+    if let Some(name) = metadata.name.as_ref() {
+        let _ = writeln!(out, "/// {name}");
+    }
+    if let Some(author) = metadata.author.as_ref() {
+        let _ = writeln!(out, "///\n/// By {author}");
+    }
+    if let Some(description) = metadata.description.as_ref() {
+        out.push_str("///\n");
+        for line in description.lines() {
+            let _ = writeln!(out, "/// {line}");
+        }
+    }
+
+    if !script.costumes.is_empty() {
+        let _ = writeln!(
+            out,
+            "// Only installed for costumes {:?}, install it with `Agent::set_costume` to keep that",
+            script.costumes
+        );
+    }
+
+    let category = match script.category {
+        Category::Game => "Game",
+        Category::Effect => "Effect",
+        Category::Sound => "Sound",
+        Category::Expression => "Expression",
+    };
+
+    let _ = writeln!(
+        out,
+        "#[smashline::acmd(agent = {}, script = {}, category = ::smashline::Acmd::{category})]",
+        attribute_hash(script.agent),
+        attribute_hash(script.replace),
+    );
+    let _ = writeln!(
+        out,
+        "unsafe extern \"C\" fn {}(agent: &mut L2CAgentBase) {{",
+        function_name(script)
+    );
+    indent(&mut out, &actions(registry, &script.actions));
+    out.push_str("}\n");
+
+    out
+}
+
+/// Generates a Rust module containing every script, along with the [`PRELUDE`] and an `install` function
+pub fn module(registry: &ActionRegistry, scripts: &[&SmashlineScript]) -> String {
+    let mut out = PRELUDE.to_string();
+
+    for script in scripts {
+        out.push('\n');
+        out.push_str(&self::script(registry, script));
+    }
+
+    out.push_str("\npub fn install() {\n");
+    for script in scripts {
+        let _ = writeln!(out, "    {}::install();", function_name(script));
+    }
+    out.push_str("}\n");
+
+    out
+}
@@ -0,0 +1,1073 @@
+//! What actions run against, so that scripts aren't tied to the game
+//!
+//! Actions make their game calls through an [`ExecutionContext`] rather than an agent. In game the
+//! context is the [`L2CAgentBase`] running the script, while [`RecordingContext`] stands in for it
+//! anywhere else, keeping a list of every call it was asked to make:
+//!
+//! ```ignore
+//! let mut context = RecordingContext::new();
+//! context.ints.insert(COUNTER, 3);
+//!
+//! ACTION_REGISTRY.execute(&action, &mut context)?;
+//! assert_eq!(context.calls, [RecordedCall::Frame(10.0), RecordedCall::OnFlag(FLAG)]);
+//! ```
+//!
+//! The values of lua constants are looked up in game as well. Anywhere else they come from a table
+//! filled in with [`set_lua_const`], and the constants missing from it fail to load.
+
+use std::collections::BTreeMap;
+
+use hash40::Hash40;
+use smash::lua2cpp::L2CAgentBase;
+
+#[cfg(not(target_os = "switch"))]
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    RwLock,
+};
+
+/// The arguments of the `ATTACK` macro, in the order it takes them
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttackParams {
+    pub id: u64,
+    pub part: u64,
+    pub bone: Hash40,
+    pub damage: f32,
+    pub angle: u64,
+    pub knockback_growth: i32,
+    pub fixed_knockback: i32,
+    pub base_knockback: i32,
+    pub size: f32,
+    pub offset: [f32; 3],
+    /// The other end of the hitbox when it is a capsule
+    pub offset2: Option<[f32; 3]>,
+    pub hitlag_mul: f32,
+    pub sdi_mul: f32,
+    pub shield_setoff: i32,
+    pub hit_direction: i32,
+    pub set_weight: bool,
+    pub shield_damage: f32,
+    pub trip_chance: f32,
+    pub rehit_rate: u32,
+    pub reflectable: bool,
+    pub absorbable: bool,
+    pub flinchless: bool,
+    pub disable_hitlag: bool,
+    pub direct: bool,
+    pub situation_mask: i32,
+    pub category_mask: i32,
+    pub part_mask: i32,
+    pub friendly_fire: bool,
+    pub collision_attribute: Hash40,
+    pub sound_level: i32,
+    pub sound: i32,
+    pub region: i32,
+}
+
+/// The arguments of the `CATCH` macro
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatchParams {
+    pub id: i32,
+    pub bone: Hash40,
+    pub size: f32,
+    pub offset: [f32; 3],
+    /// The other end of the grab box when it is a capsule
+    pub offset2: Option<[f32; 3]>,
+    /// The status the grabbed target is put in
+    pub status: i32,
+    pub situation_mask: i32,
+}
+
+/// The arguments of the `ATTACK_ABS` macro that change between throws, the others are the same
+/// for every throw in the game
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttackAbsParams {
+    pub kind: i32,
+    pub id: i32,
+    pub damage: f32,
+    pub angle: u64,
+    pub knockback_growth: i32,
+    pub fixed_knockback: i32,
+    pub base_knockback: i32,
+    pub hit_direction: i32,
+    pub collision_attribute: Hash40,
+    pub sound_level: i32,
+    pub sound: i32,
+    pub region: i32,
+}
+
+impl AttackAbsParams {
+    /// The two arguments of `ATTACK_ABS` after `base_knockback`, which every throw in the game
+    /// passes the same values for
+    pub const AFTER_KNOCKBACK: (f32, f32) = (0.0, 1.0);
+    /// The two arguments of `ATTACK_ABS` after `hit_direction`, which every throw in the game passes
+    /// the same values for
+    pub const AFTER_HIT_DIRECTION: (f32, bool) = (0.0, true);
+}
+
+/// The arguments of the `EFFECT` and `LANDING_EFFECT` macros
+#[derive(Debug, Clone, PartialEq)]
+pub struct EffectParams {
+    pub effect: Hash40,
+    pub bone: Hash40,
+    pub offset: [f32; 3],
+    pub rotation: [f32; 3],
+    pub size: f32,
+    /// How far the offset is randomly moved in each direction
+    pub offset_range: [f32; 3],
+    /// How far the rotation is randomly moved around each axis
+    pub rotation_range: [f32; 3],
+    pub terminate: bool,
+}
+
+/// The arguments of the `EFFECT_FOLLOW` macro
+#[derive(Debug, Clone, PartialEq)]
+pub struct EffectFollowParams {
+    pub effect: Hash40,
+    pub bone: Hash40,
+    pub offset: [f32; 3],
+    pub rotation: [f32; 3],
+    pub size: f32,
+    pub terminate: bool,
+}
+
+/// The state of a hurtbox, which decides what happens when an attack overlaps it
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HitStatus {
+    Normal,
+    /// Intangible, attacks pass through it without hitting
+    Xlu,
+    /// Attacks hit it without dealing damage or knockback
+    Invincible,
+    /// The hurtbox is turned off
+    Off,
+}
+
+/// The game calls that actions make, see the [module documentation](self)
+///
+/// Work variables and hitbox ids are the values of their lua constants. Only the timing and work
+/// variable methods have to be implemented, the game calls after them do nothing unless a context
+/// overrides them.
+#[allow(unused_variables)]
+pub trait ExecutionContext {
+    /// The agent running the script, for actions that need more of the game than this trait
+    /// covers. `None` when the script isn't running in game.
+    fn agent(&mut self) -> Option<&mut L2CAgentBase> {
+        None
+    }
+
+    /// Waits until the script reaches `frame`, the same as `frame` in ACMD
+    fn frame(&mut self, frame: f32);
+
+    /// Waits for `frames` frames, the same as `wait` in ACMD
+    fn wait(&mut self, frames: f32);
+
+    /// False while the script is only being run to look for its frames
+    fn is_excute(&mut self) -> bool;
+
+    fn on_flag(&mut self, flag: i32);
+    fn off_flag(&mut self, flag: i32);
+    fn set_flag(&mut self, flag: i32, value: bool);
+    fn is_flag(&mut self, flag: i32) -> bool;
+    fn set_int(&mut self, variable: i32, value: i32);
+    fn get_int(&mut self, variable: i32) -> i32;
+    fn set_float(&mut self, variable: i32, value: f32);
+    fn get_float(&mut self, variable: i32) -> f32;
+
+    /// Places a hitbox, or replaces the one with the same id
+    fn attack(&mut self, params: &AttackParams) {}
+    fn attack_clear(&mut self, id: i32) {}
+    fn attack_clear_all(&mut self) {}
+    fn set_add_reaction_frame_revised(&mut self, id: i32, frames: f32) {}
+    fn set_vec_target_pos(&mut self, id: i32, target: Hash40, offset: [f32; 2], frames: u32) {}
+    /// Places a grab box, or replaces the one with the same id
+    fn catch(&mut self, params: &CatchParams) {}
+    fn catch_clear_all(&mut self) {}
+    /// Sets the hit a throw deals to the grabbed target, without dealing it yet
+    fn attack_abs(&mut self, params: &AttackAbsParams) {}
+    /// Deals the hit of `kind` set with `attack_abs` to the target held at `bone`, which is the
+    /// target stored in the throw's work variables
+    fn atk_hit_abs(&mut self, kind: i32, bone: Hash40) {}
+
+    /// Sets the status of every hurtbox
+    fn set_hit_status_all(&mut self, status: HitStatus) {}
+    /// Sets the status of the whole agent, which takes priority over the status of each hurtbox
+    fn set_hit_whole(&mut self, status: HitStatus) {}
+    /// Sets the status of the hurtbox on `bone`, the same as `HIT_NODE` in ACMD
+    fn hit_node(&mut self, bone: Hash40, status: HitStatus) {}
+    /// Sets the status of the hurtbox with the index `part`, the same as `HIT_NO` in ACMD
+    fn hit_no(&mut self, part: i32, status: HitStatus) {}
+    /// Puts every hurtbox changed with `hit_node` or `hit_no` back to normal
+    fn hit_reset_all(&mut self) {}
+    /// Turns the pushing between the agent and other fighters on or off
+    fn set_body_collision(&mut self, enabled: bool) {}
+    /// Turns a shield or reflector box on or off, the same as `shield!` in ACMD
+    fn shield(&mut self, enabled: bool, kind: i32, id: i32, group: i32) {}
+
+    fn effect(&mut self, params: &EffectParams) {}
+    fn effect_follow(&mut self, params: &EffectFollowParams) {}
+    fn landing_effect(&mut self, params: &EffectParams) {}
+    /// Colors the effect made by the last effect call
+    fn last_effect_set_color(&mut self, color: [f32; 3]) {}
+    /// Sets the playback rate of the effect made by the last effect call
+    fn last_effect_set_rate(&mut self, rate: f32) {}
+    fn effect_off_kind(&mut self, effect: Hash40, deactivate: bool, immediate: bool) {}
+
+    fn play_se(&mut self, sound: Hash40) {}
+    /// Plays a sound that keeps playing after the animation changes
+    fn play_se_remain(&mut self, sound: Hash40) {}
+    /// Plays one of the sounds of a sequence, such as the voice clips of an attack
+    fn play_sequence(&mut self, sequence: Hash40) {}
+    /// Plays a sound that stops when the agent's status changes
+    fn play_status(&mut self, sound: Hash40) {}
+    fn stop_se(&mut self, sound: Hash40) {}
+
+    /// Rumbles the controller of the player the agent belongs to
+    fn set_rumble(&mut self, kind: Hash40, frames: i32, looping: bool) {}
+    /// Changes how the agent is tilted to match the ground, the same as `slope!` in ACMD
+    fn slope(&mut self, kind: i32, status: i32) {}
+    /// Shows or hides the whole model
+    fn set_visibility_whole(&mut self, visible: bool) {}
+    /// Switches the mesh group `group` of the model to `state`
+    fn set_visibility(&mut self, group: Hash40, state: Hash40) {}
+    /// Shows or hides the item the agent is holding
+    fn set_have_item_visibility(&mut self, visible: bool) {}
+
+    /// Sets the speed the animation, and so the script, plays at
+    fn ft_motion_rate(&mut self, rate: f32) {}
+    /// Sets the motion rate so that the next `frames` frames of the script take `desired` frames
+    fn ft_desired_rate(&mut self, frames: f32, desired: f32) {}
+    /// Plays the frames of the script from `start` to `end` over `desired` frames
+    fn ft_motion_rate_range(&mut self, start: f32, end: f32, desired: f32) {}
+}
+
+/// The value of a lua constant by its name, such as `FIGHTER_STATUS_ATTACK_FLAG_ENABLE_COMBO`
+#[cfg(target_os = "switch")]
+pub fn lua_const(name: &str) -> Option<i32> {
+    let hash = lua_bind_hash::lua_bind_hash_str(name);
+    let mut value = 0i32;
+    unsafe { smash::lib::lua_bind_get_value(hash, &mut value) }.then_some(value)
+}
+
+#[cfg(not(target_os = "switch"))]
+static LUA_CONSTS: RwLock<BTreeMap<String, i32>> = RwLock::new(BTreeMap::new());
+
+#[cfg(not(target_os = "switch"))]
+static UNKNOWN_LUA_CONSTS_AS_ZERO: AtomicBool = AtomicBool::new(false);
+
+/// The value of a lua constant by its name, from the ones set with [`set_lua_const`]. Constants
+/// that weren't set are unknown, unless [`set_unknown_lua_consts_as_zero`] was turned on.
+#[cfg(not(target_os = "switch"))]
+pub fn lua_const(name: &str) -> Option<i32> {
+    LUA_CONSTS
+        .read()
+        .unwrap()
+        .get(name)
+        .copied()
+        .or_else(|| UNKNOWN_LUA_CONSTS_AS_ZERO.load(Ordering::Relaxed).then_some(0))
+}
+
+/// Sets the value of a lua constant outside of the game, where it can't be looked up
+#[cfg(not(target_os = "switch"))]
+pub fn set_lua_const(name: impl Into<String>, value: i32) {
+    LUA_CONSTS.write().unwrap().insert(name.into(), value);
+}
+
+/// Makes the constants that weren't set with [`set_lua_const`] read as `0` instead of failing to
+/// load, for tools that load scripts without knowing the values of their constants
+#[cfg(not(target_os = "switch"))]
+pub fn set_unknown_lua_consts_as_zero(enabled: bool) {
+    UNKNOWN_LUA_CONSTS_AS_ZERO.store(enabled, Ordering::Relaxed);
+}
+
+#[cfg(target_os = "switch")]
+mod game {
+    use smash::{
+        app::{
+            lua_bind::{
+                AttackModule, CatchModule, ControlModule, HitModule, ItemModule, JostleModule,
+                VisibilityModule, WorkModule,
+            },
+            sv_animcmd, HitStatus as GameHitStatus,
+        },
+        lib::lua_const::{
+            BATTLE_OBJECT_ID_INVALID, FIGHTER_STATUS_THROW_WORK_INT_TARGET_HIT_GROUP,
+            FIGHTER_STATUS_THROW_WORK_INT_TARGET_HIT_NO,
+            FIGHTER_STATUS_THROW_WORK_INT_TARGET_OBJECT, HIT_STATUS_INVINCIBLE, HIT_STATUS_NORMAL,
+            HIT_STATUS_OFF, HIT_STATUS_XLU, MA_MSC_CMD_SHIELD_OFF, MA_MSC_CMD_SHIELD_ON,
+        },
+        phx::{Hash40 as GameHash40, Vector2f},
+    };
+
+    use super::*;
+
+    fn hit_status(status: HitStatus) -> i32 {
+        match status {
+            HitStatus::Normal => *HIT_STATUS_NORMAL,
+            HitStatus::Xlu => *HIT_STATUS_XLU,
+            HitStatus::Invincible => *HIT_STATUS_INVINCIBLE,
+            HitStatus::Off => *HIT_STATUS_OFF,
+        }
+    }
+
+    fn game_hash(hash: Hash40) -> GameHash40 {
+        GameHash40::new_raw(hash.0)
+    }
+
+    impl ExecutionContext for L2CAgentBase {
+        fn agent(&mut self) -> Option<&mut L2CAgentBase> {
+            Some(self)
+        }
+
+        fn frame(&mut self, frame: f32) {
+            unsafe { sv_animcmd::frame(self.lua_state_agent, frame) };
+        }
+
+        fn wait(&mut self, frames: f32) {
+            unsafe { sv_animcmd::wait(self.lua_state_agent, frames) };
+        }
+
+        fn is_excute(&mut self) -> bool {
+            unsafe { sv_animcmd::is_excute(self.lua_state_agent) }
+        }
+
+        fn on_flag(&mut self, flag: i32) {
+            unsafe { WorkModule::on_flag(self.module_accessor, flag) };
+        }
+
+        fn off_flag(&mut self, flag: i32) {
+            unsafe { WorkModule::off_flag(self.module_accessor, flag) };
+        }
+
+        fn set_flag(&mut self, flag: i32, value: bool) {
+            unsafe { WorkModule::set_flag(self.module_accessor, value, flag) };
+        }
+
+        fn is_flag(&mut self, flag: i32) -> bool {
+            unsafe { WorkModule::is_flag(self.module_accessor, flag) }
+        }
+
+        fn set_int(&mut self, variable: i32, value: i32) {
+            unsafe { WorkModule::set_int(self.module_accessor, value, variable) };
+        }
+
+        fn get_int(&mut self, variable: i32) -> i32 {
+            unsafe { WorkModule::get_int(self.module_accessor, variable) }
+        }
+
+        fn set_float(&mut self, variable: i32, value: f32) {
+            unsafe { WorkModule::set_float(self.module_accessor, value, variable) };
+        }
+
+        fn get_float(&mut self, variable: i32) -> f32 {
+            unsafe { WorkModule::get_float(self.module_accessor, variable) }
+        }
+
+        fn attack(&mut self, params: &AttackParams) {
+            let [x, y, z] = params.offset;
+            let [x2, y2, z2] = match params.offset2 {
+                Some(offset) => offset.map(Some),
+                None => [None; 3],
+            };
+
+            unsafe {
+                smash_script::macros::ATTACK(
+                    self,
+                    params.id,
+                    params.part,
+                    game_hash(params.bone),
+                    params.damage,
+                    params.angle,
+                    params.knockback_growth,
+                    params.fixed_knockback,
+                    params.base_knockback,
+                    params.size,
+                    x,
+                    y,
+                    z,
+                    x2,
+                    y2,
+                    z2,
+                    params.hitlag_mul,
+                    params.sdi_mul,
+                    params.shield_setoff,
+                    params.hit_direction,
+                    params.set_weight,
+                    params.shield_damage,
+                    params.trip_chance,
+                    params.rehit_rate,
+                    params.reflectable,
+                    params.absorbable,
+                    params.flinchless,
+                    params.disable_hitlag,
+                    params.direct,
+                    params.situation_mask,
+                    params.category_mask,
+                    params.part_mask,
+                    params.friendly_fire,
+                    game_hash(params.collision_attribute),
+                    params.sound_level,
+                    params.sound,
+                    params.region,
+                )
+            };
+        }
+
+        fn attack_clear(&mut self, id: i32) {
+            unsafe { AttackModule::clear(self.module_accessor, id, false) };
+        }
+
+        fn attack_clear_all(&mut self) {
+            unsafe { AttackModule::clear_all(self.module_accessor) };
+        }
+
+        fn set_add_reaction_frame_revised(&mut self, id: i32, frames: f32) {
+            unsafe {
+                AttackModule::set_add_reaction_frame_revised(
+                    self.module_accessor,
+                    id,
+                    frames,
+                    false,
+                )
+            };
+        }
+
+        fn set_vec_target_pos(&mut self, id: i32, target: Hash40, offset: [f32; 2], frames: u32) {
+            let [x, y] = offset;
+            unsafe {
+                AttackModule::set_vec_target_pos(
+                    self.module_accessor,
+                    id,
+                    game_hash(target),
+                    &Vector2f { x, y },
+                    frames as _,
+                    false,
+                )
+            };
+        }
+
+        fn catch(&mut self, params: &CatchParams) {
+            let [x, y, z] = params.offset;
+            let [x2, y2, z2] = match params.offset2 {
+                Some(offset) => offset.map(Some),
+                None => [None; 3],
+            };
+
+            unsafe {
+                smash_script::macros::CATCH(
+                    self,
+                    params.id as _,
+                    game_hash(params.bone),
+                    params.size,
+                    x,
+                    y,
+                    z,
+                    x2,
+                    y2,
+                    z2,
+                    params.status,
+                    params.situation_mask as _,
+                )
+            };
+        }
+
+        fn catch_clear_all(&mut self) {
+            unsafe { CatchModule::clear_all(self.module_accessor) };
+        }
+
+        fn attack_abs(&mut self, params: &AttackAbsParams) {
+            let (arg8, arg9) = AttackAbsParams::AFTER_KNOCKBACK;
+            let (arg11, arg12) = AttackAbsParams::AFTER_HIT_DIRECTION;
+            unsafe {
+                smash_script::macros::ATTACK_ABS(
+                    self,
+                    params.kind,
+                    params.id as _,
+                    params.damage,
+                    params.angle as _,
+                    params.knockback_growth,
+                    params.fixed_knockback,
+                    params.base_knockback,
+                    arg8,
+                    arg9,
+                    params.hit_direction,
+                    arg11,
+                    arg12,
+                    game_hash(params.collision_attribute),
+                    params.sound_level,
+                    params.sound,
+                    params.region,
+                )
+            };
+        }
+
+        fn atk_hit_abs(&mut self, kind: i32, bone: Hash40) {
+            unsafe {
+                let target = |variable| WorkModule::get_int64(self.module_accessor, variable);
+                let object = target(*FIGHTER_STATUS_THROW_WORK_INT_TARGET_OBJECT);
+                let group = target(*FIGHTER_STATUS_THROW_WORK_INT_TARGET_HIT_GROUP);
+                let no = target(*FIGHTER_STATUS_THROW_WORK_INT_TARGET_HIT_NO);
+                smash_script::macros::ATK_HIT_ABS(self, kind, game_hash(bone), object, group, no)
+            };
+        }
+
+        fn set_hit_status_all(&mut self, status: HitStatus) {
+            unsafe {
+                HitModule::set_status_all(
+                    self.module_accessor,
+                    GameHitStatus(hit_status(status)),
+                    0,
+                )
+            };
+        }
+
+        fn set_hit_whole(&mut self, status: HitStatus) {
+            unsafe {
+                HitModule::set_whole(self.module_accessor, GameHitStatus(hit_status(status)), 0)
+            };
+        }
+
+        fn hit_node(&mut self, bone: Hash40, status: HitStatus) {
+            unsafe { smash_script::macros::HIT_NODE(self, game_hash(bone), hit_status(status)) };
+        }
+
+        fn hit_no(&mut self, part: i32, status: HitStatus) {
+            unsafe { smash_script::macros::HIT_NO(self, part as _, hit_status(status)) };
+        }
+
+        fn hit_reset_all(&mut self) {
+            unsafe { smash_script::macros::HIT_RESET_ALL(self) };
+        }
+
+        fn set_body_collision(&mut self, enabled: bool) {
+            unsafe { JostleModule::set_status(self.module_accessor, enabled) };
+        }
+
+        fn shield(&mut self, enabled: bool, kind: i32, id: i32, group: i32) {
+            unsafe {
+                if enabled {
+                    smash_script::shield!(self, *MA_MSC_CMD_SHIELD_ON, kind, id, group)
+                } else {
+                    smash_script::shield!(self, *MA_MSC_CMD_SHIELD_OFF, kind, id, group)
+                }
+            };
+        }
+
+        fn effect(&mut self, params: &EffectParams) {
+            let [x, y, z] = params.offset;
+            let [rx, ry, rz] = params.rotation;
+            let [range_x, range_y, range_z] = params.offset_range;
+            let [range_rx, range_ry, range_rz] = params.rotation_range;
+            unsafe {
+                smash_script::macros::EFFECT(
+                    self,
+                    game_hash(params.effect),
+                    game_hash(params.bone),
+                    x,
+                    y,
+                    z,
+                    rx,
+                    ry,
+                    rz,
+                    params.size,
+                    range_x,
+                    range_y,
+                    range_z,
+                    range_rx,
+                    range_ry,
+                    range_rz,
+                    params.terminate,
+                )
+            };
+        }
+
+        fn effect_follow(&mut self, params: &EffectFollowParams) {
+            let [x, y, z] = params.offset;
+            let [rx, ry, rz] = params.rotation;
+            unsafe {
+                smash_script::macros::EFFECT_FOLLOW(
+                    self,
+                    game_hash(params.effect),
+                    game_hash(params.bone),
+                    x,
+                    y,
+                    z,
+                    rx,
+                    ry,
+                    rz,
+                    params.size,
+                    params.terminate,
+                )
+            };
+        }
+
+        fn landing_effect(&mut self, params: &EffectParams) {
+            let [x, y, z] = params.offset;
+            let [rx, ry, rz] = params.rotation;
+            let [range_x, range_y, range_z] = params.offset_range;
+            let [range_rx, range_ry, range_rz] = params.rotation_range;
+            unsafe {
+                smash_script::macros::LANDING_EFFECT(
+                    self,
+                    game_hash(params.effect),
+                    game_hash(params.bone),
+                    x,
+                    y,
+                    z,
+                    rx,
+                    ry,
+                    rz,
+                    params.size,
+                    range_x,
+                    range_y,
+                    range_z,
+                    range_rx,
+                    range_ry,
+                    range_rz,
+                    params.terminate,
+                )
+            };
+        }
+
+        fn last_effect_set_color(&mut self, color: [f32; 3]) {
+            let [r, g, b] = color;
+            unsafe { smash_script::macros::LAST_EFFECT_SET_COLOR(self, r, g, b) };
+        }
+
+        fn last_effect_set_rate(&mut self, rate: f32) {
+            unsafe { smash_script::macros::LAST_EFFECT_SET_RATE(self, rate) };
+        }
+
+        fn effect_off_kind(&mut self, effect: Hash40, deactivate: bool, immediate: bool) {
+            unsafe {
+                smash_script::macros::EFFECT_OFF_KIND(
+                    self,
+                    game_hash(effect),
+                    deactivate,
+                    immediate,
+                )
+            };
+        }
+
+        fn play_se(&mut self, sound: Hash40) {
+            unsafe { smash_script::macros::PLAY_SE(self, game_hash(sound)) };
+        }
+
+        fn play_se_remain(&mut self, sound: Hash40) {
+            unsafe { smash_script::macros::PLAY_SE_REMAIN(self, game_hash(sound)) };
+        }
+
+        fn play_sequence(&mut self, sequence: Hash40) {
+            unsafe { smash_script::macros::PLAY_SEQUENCE(self, game_hash(sequence)) };
+        }
+
+        fn play_status(&mut self, sound: Hash40) {
+            unsafe { smash_script::macros::PLAY_STATUS(self, game_hash(sound)) };
+        }
+
+        fn stop_se(&mut self, sound: Hash40) {
+            unsafe { smash_script::macros::STOP_SE(self, game_hash(sound)) };
+        }
+
+        fn set_rumble(&mut self, kind: Hash40, frames: i32, looping: bool) {
+            unsafe {
+                ControlModule::set_rumble(
+                    self.module_accessor,
+                    game_hash(kind),
+                    frames,
+                    looping,
+                    *BATTLE_OBJECT_ID_INVALID as u32,
+                )
+            };
+        }
+
+        fn slope(&mut self, kind: i32, status: i32) {
+            unsafe { smash_script::slope!(self, kind, status) };
+        }
+
+        fn set_visibility_whole(&mut self, visible: bool) {
+            unsafe { VisibilityModule::set_whole(self.module_accessor, visible) };
+        }
+
+        fn set_visibility(&mut self, group: Hash40, state: Hash40) {
+            unsafe {
+                VisibilityModule::set_int64(self.module_accessor, group.0 as i64, state.0 as i64)
+            };
+        }
+
+        fn set_have_item_visibility(&mut self, visible: bool) {
+            unsafe { ItemModule::set_have_item_visibility(self.module_accessor, visible, 0) };
+        }
+
+        fn ft_motion_rate(&mut self, rate: f32) {
+            unsafe { smash_script::macros::FT_MOTION_RATE(self, rate) };
+        }
+
+        fn ft_desired_rate(&mut self, frames: f32, desired: f32) {
+            unsafe { smash_script::macros::FT_DESIRED_RATE(self, frames, desired) };
+        }
+
+        fn ft_motion_rate_range(&mut self, start: f32, end: f32, desired: f32) {
+            unsafe { smash_script::macros::FT_MOTION_RATE_RANGE(self, start, end, desired) };
+        }
+    }
+}
+
+/// A game call made through a [`RecordingContext`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordedCall {
+    Frame(f32),
+    Wait(f32),
+    OnFlag(i32),
+    OffFlag(i32),
+    SetFlag {
+        flag: i32,
+        value: bool,
+    },
+    SetInt {
+        variable: i32,
+        value: i32,
+    },
+    SetFloat {
+        variable: i32,
+        value: f32,
+    },
+    Attack(AttackParams),
+    AttackClear(i32),
+    AttackClearAll,
+    SetAddReactionFrameRevised {
+        id: i32,
+        frames: f32,
+    },
+    SetVecTargetPos {
+        id: i32,
+        target: Hash40,
+        offset: [f32; 2],
+        frames: u32,
+    },
+    Catch(CatchParams),
+    CatchClearAll,
+    AttackAbs(AttackAbsParams),
+    AtkHitAbs {
+        kind: i32,
+        bone: Hash40,
+    },
+    SetHitStatusAll(HitStatus),
+    SetHitWhole(HitStatus),
+    HitNode {
+        bone: Hash40,
+        status: HitStatus,
+    },
+    HitNo {
+        part: i32,
+        status: HitStatus,
+    },
+    HitResetAll,
+    SetBodyCollision(bool),
+    Shield {
+        enabled: bool,
+        kind: i32,
+        id: i32,
+        group: i32,
+    },
+    Effect(EffectParams),
+    EffectFollow(EffectFollowParams),
+    LandingEffect(EffectParams),
+    LastEffectSetColor([f32; 3]),
+    LastEffectSetRate(f32),
+    EffectOffKind {
+        effect: Hash40,
+        deactivate: bool,
+        immediate: bool,
+    },
+    PlaySe(Hash40),
+    PlaySeRemain(Hash40),
+    PlaySequence(Hash40),
+    PlayStatus(Hash40),
+    StopSe(Hash40),
+    SetRumble {
+        kind: Hash40,
+        frames: i32,
+        looping: bool,
+    },
+    Slope {
+        kind: i32,
+        status: i32,
+    },
+    SetVisibilityWhole(bool),
+    SetVisibility {
+        group: Hash40,
+        state: Hash40,
+    },
+    SetHaveItemVisibility(bool),
+    FtMotionRate(f32),
+    FtDesiredRate {
+        frames: f32,
+        desired: f32,
+    },
+    FtMotionRateRange {
+        start: f32,
+        end: f32,
+        desired: f32,
+    },
+}
+
+/// A context that records the calls made to it instead of making them, for running scripts
+/// outside of the game
+///
+/// Its work variables start out as `0` and `false` and hold whatever the script writes to them, and
+/// reads aren't recorded. `frame` and `wait` move [`RecordingContext::frame`] forward.
+#[derive(Debug, Clone)]
+pub struct RecordingContext {
+    pub calls: Vec<RecordedCall>,
+    /// The frame the script has waited until
+    pub frame: f32,
+    /// What `is_excute` returns, `true` unless set otherwise
+    pub excute: bool,
+    pub flags: BTreeMap<i32, bool>,
+    pub ints: BTreeMap<i32, i32>,
+    pub floats: BTreeMap<i32, f32>,
+}
+
+impl Default for RecordingContext {
+    fn default() -> Self {
+        Self {
+            calls: vec![],
+            frame: 0.0,
+            excute: true,
+            flags: BTreeMap::new(),
+            ints: BTreeMap::new(),
+            floats: BTreeMap::new(),
+        }
+    }
+}
+
+impl RecordingContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes the calls recorded so far, leaving the list empty
+    pub fn take_calls(&mut self) -> Vec<RecordedCall> {
+        std::mem::take(&mut self.calls)
+    }
+}
+
+impl ExecutionContext for RecordingContext {
+    fn frame(&mut self, frame: f32) {
+        self.frame = self.frame.max(frame);
+        self.calls.push(RecordedCall::Frame(frame));
+    }
+
+    fn wait(&mut self, frames: f32) {
+        self.frame += frames;
+        self.calls.push(RecordedCall::Wait(frames));
+    }
+
+    fn is_excute(&mut self) -> bool {
+        self.excute
+    }
+
+    fn on_flag(&mut self, flag: i32) {
+        self.flags.insert(flag, true);
+        self.calls.push(RecordedCall::OnFlag(flag));
+    }
+
+    fn off_flag(&mut self, flag: i32) {
+        self.flags.insert(flag, false);
+        self.calls.push(RecordedCall::OffFlag(flag));
+    }
+
+    fn set_flag(&mut self, flag: i32, value: bool) {
+        self.flags.insert(flag, value);
+        self.calls.push(RecordedCall::SetFlag { flag, value });
+    }
+
+    fn is_flag(&mut self, flag: i32) -> bool {
+        self.flags.get(&flag).copied().unwrap_or(false)
+    }
+
+    fn set_int(&mut self, variable: i32, value: i32) {
+        self.ints.insert(variable, value);
+        self.calls.push(RecordedCall::SetInt { variable, value });
+    }
+
+    fn get_int(&mut self, variable: i32) -> i32 {
+        self.ints.get(&variable).copied().unwrap_or(0)
+    }
+
+    fn set_float(&mut self, variable: i32, value: f32) {
+        self.floats.insert(variable, value);
+        self.calls.push(RecordedCall::SetFloat { variable, value });
+    }
+
+    fn get_float(&mut self, variable: i32) -> f32 {
+        self.floats.get(&variable).copied().unwrap_or(0.0)
+    }
+
+    fn attack(&mut self, params: &AttackParams) {
+        self.calls.push(RecordedCall::Attack(params.clone()));
+    }
+
+    fn attack_clear(&mut self, id: i32) {
+        self.calls.push(RecordedCall::AttackClear(id));
+    }
+
+    fn attack_clear_all(&mut self) {
+        self.calls.push(RecordedCall::AttackClearAll);
+    }
+
+    fn set_add_reaction_frame_revised(&mut self, id: i32, frames: f32) {
+        self.calls
+            .push(RecordedCall::SetAddReactionFrameRevised { id, frames });
+    }
+
+    fn set_vec_target_pos(&mut self, id: i32, target: Hash40, offset: [f32; 2], frames: u32) {
+        self.calls.push(RecordedCall::SetVecTargetPos {
+            id,
+            target,
+            offset,
+            frames,
+        });
+    }
+
+    fn catch(&mut self, params: &CatchParams) {
+        self.calls.push(RecordedCall::Catch(params.clone()));
+    }
+
+    fn catch_clear_all(&mut self) {
+        self.calls.push(RecordedCall::CatchClearAll);
+    }
+
+    fn attack_abs(&mut self, params: &AttackAbsParams) {
+        self.calls.push(RecordedCall::AttackAbs(params.clone()));
+    }
+
+    fn atk_hit_abs(&mut self, kind: i32, bone: Hash40) {
+        self.calls.push(RecordedCall::AtkHitAbs { kind, bone });
+    }
+
+    fn set_hit_status_all(&mut self, status: HitStatus) {
+        self.calls.push(RecordedCall::SetHitStatusAll(status));
+    }
+
+    fn set_hit_whole(&mut self, status: HitStatus) {
+        self.calls.push(RecordedCall::SetHitWhole(status));
+    }
+
+    fn hit_node(&mut self, bone: Hash40, status: HitStatus) {
+        self.calls.push(RecordedCall::HitNode { bone, status });
+    }
+
+    fn hit_no(&mut self, part: i32, status: HitStatus) {
+        self.calls.push(RecordedCall::HitNo { part, status });
+    }
+
+    fn hit_reset_all(&mut self) {
+        self.calls.push(RecordedCall::HitResetAll);
+    }
+
+    fn set_body_collision(&mut self, enabled: bool) {
+        self.calls.push(RecordedCall::SetBodyCollision(enabled));
+    }
+
+    fn shield(&mut self, enabled: bool, kind: i32, id: i32, group: i32) {
+        self.calls.push(RecordedCall::Shield {
+            enabled,
+            kind,
+            id,
+            group,
+        });
+    }
+
+    fn effect(&mut self, params: &EffectParams) {
+        self.calls.push(RecordedCall::Effect(params.clone()));
+    }
+
+    fn effect_follow(&mut self, params: &EffectFollowParams) {
+        self.calls.push(RecordedCall::EffectFollow(params.clone()));
+    }
+
+    fn landing_effect(&mut self, params: &EffectParams) {
+        self.calls.push(RecordedCall::LandingEffect(params.clone()));
+    }
+
+    fn last_effect_set_color(&mut self, color: [f32; 3]) {
+        self.calls.push(RecordedCall::LastEffectSetColor(color));
+    }
+
+    fn last_effect_set_rate(&mut self, rate: f32) {
+        self.calls.push(RecordedCall::LastEffectSetRate(rate));
+    }
+
+    fn effect_off_kind(&mut self, effect: Hash40, deactivate: bool, immediate: bool) {
+        self.calls.push(RecordedCall::EffectOffKind {
+            effect,
+            deactivate,
+            immediate,
+        });
+    }
+
+    fn play_se(&mut self, sound: Hash40) {
+        self.calls.push(RecordedCall::PlaySe(sound));
+    }
+
+    fn play_se_remain(&mut self, sound: Hash40) {
+        self.calls.push(RecordedCall::PlaySeRemain(sound));
+    }
+
+    fn play_sequence(&mut self, sequence: Hash40) {
+        self.calls.push(RecordedCall::PlaySequence(sequence));
+    }
+
+    fn play_status(&mut self, sound: Hash40) {
+        self.calls.push(RecordedCall::PlayStatus(sound));
+    }
+
+    fn stop_se(&mut self, sound: Hash40) {
+        self.calls.push(RecordedCall::StopSe(sound));
+    }
+
+    fn set_rumble(&mut self, kind: Hash40, frames: i32, looping: bool) {
+        self.calls.push(RecordedCall::SetRumble {
+            kind,
+            frames,
+            looping,
+        });
+    }
+
+    fn slope(&mut self, kind: i32, status: i32) {
+        self.calls.push(RecordedCall::Slope { kind, status });
+    }
+
+    fn set_visibility_whole(&mut self, visible: bool) {
+        self.calls.push(RecordedCall::SetVisibilityWhole(visible));
+    }
+
+    fn set_visibility(&mut self, group: Hash40, state: Hash40) {
+        self.calls
+            .push(RecordedCall::SetVisibility { group, state });
+    }
+
+    fn set_have_item_visibility(&mut self, visible: bool) {
+        self.calls
+            .push(RecordedCall::SetHaveItemVisibility(visible));
+    }
+
+    fn ft_motion_rate(&mut self, rate: f32) {
+        self.calls.push(RecordedCall::FtMotionRate(rate));
+    }
+
+    fn ft_desired_rate(&mut self, frames: f32, desired: f32) {
+        self.calls
+            .push(RecordedCall::FtDesiredRate { frames, desired });
+    }
+
+    fn ft_motion_rate_range(&mut self, start: f32, end: f32, desired: f32) {
+        self.calls.push(RecordedCall::FtMotionRateRange {
+            start,
+            end,
+            desired,
+        });
+    }
+}
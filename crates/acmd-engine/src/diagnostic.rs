@@ -0,0 +1,259 @@
+//! Locating the actions which failed to load in a script file
+//!
+//! Errors from nested actions are wrapped in [`ActionError::Nested`] with the path leading to them,
+//! which is turned into a [`Diagnostic`] per bad action once the whole script has been converted.
+
+use std::fmt::Display;
+
+use crate::action::ActionError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// Formats a path as a JSON pointer, such as `/actions/3/If/then/0`
+pub struct JsonPointer<'a>(pub &'a [PathSegment]);
+
+impl Display for JsonPointer<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for segment in self.0 {
+            match segment {
+                PathSegment::Key(key) => write!(f, "/{}", key.replace('~', "~0").replace('/', "~1"))?,
+                PathSegment::Index(index) => write!(f, "/{index}")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A single action that failed to load
+#[derive(Debug)]
+pub struct Diagnostic {
+    /// The index of the top level action, or patch operation, that contains the error
+    pub index: usize,
+
+    /// The path from the root of the script to the action that failed
+    pub path: Vec<PathSegment>,
+
+    /// The 1-based line and column of the action, only known when loading from JSON text
+    pub position: Option<(usize, usize)>,
+
+    pub error: ActionError,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some((line, column)) = self.position {
+            write!(f, "line {line}, column {column} ")?;
+        }
+
+        write!(
+            f,
+            "(action {}, {}): {}",
+            self.index,
+            JsonPointer(&self.path),
+            self.error
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct Diagnostics(pub Vec<Diagnostic>);
+
+impl Display for Diagnostics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} invalid action(s)", self.0.len())?;
+        for diagnostic in self.0.iter() {
+            write!(f, "\n    {diagnostic}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Diagnostics {
+    /// Splits the error from a top level action into one diagnostic per failing action
+    pub(crate) fn push(&mut self, index: usize, error: ActionError) {
+        let root = vec![
+            PathSegment::Key("actions".to_string()),
+            PathSegment::Index(index),
+        ];
+
+        self.push_at(index, root, error);
+    }
+
+    /// Same as [`Diagnostics::push`], for an action found at `root` instead of the script's actions
+    pub(crate) fn push_at(&mut self, index: usize, root: Vec<PathSegment>, error: ActionError) {
+        flatten(root, error, &mut |path, error| {
+            self.0.push(Diagnostic {
+                index,
+                path,
+                position: None,
+                error,
+            })
+        });
+    }
+
+    /// Adds the diagnostics of a script found at `root`, such as a script inside of a bundle
+    pub(crate) fn append_at(&mut self, root: &[PathSegment], diagnostics: Diagnostics) {
+        for mut diagnostic in diagnostics.0 {
+            diagnostic.path.splice(0..0, root.iter().cloned());
+            self.0.push(diagnostic);
+        }
+    }
+
+    /// Fills in the line and column of every diagnostic from the JSON the script was parsed from
+    pub(crate) fn locate(&mut self, source: &str) {
+        for diagnostic in self.0.iter_mut() {
+            diagnostic.position = locate(source, &diagnostic.path);
+        }
+    }
+}
+
+fn flatten(
+    mut path: Vec<PathSegment>,
+    error: ActionError,
+    f: &mut dyn FnMut(Vec<PathSegment>, ActionError),
+) {
+    match error {
+        ActionError::Nested {
+            path: nested,
+            error,
+        } => {
+            path.extend(nested);
+            flatten(path, *error, f);
+        }
+        ActionError::Multiple(errors) => {
+            for error in errors {
+                flatten(path.clone(), error, f);
+            }
+        }
+        error => f(path, error),
+    }
+}
+
+struct Scanner<'a> {
+    source: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.source.get(self.offset).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.offset += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        self.skip_whitespace();
+        (self.peek()? == byte).then(|| self.offset += 1)
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.expect(b'"')?;
+        let start = self.offset;
+        loop {
+            match self.peek()? {
+                b'"' => break,
+                b'\\' => self.offset += 2,
+                _ => self.offset += 1,
+            }
+        }
+
+        let raw = std::str::from_utf8(&self.source[start..self.offset]).ok()?;
+        self.offset += 1;
+
+        // Keys are compared after unescaping, which serde_json can do for us
+        serde_json::from_str(&format!("\"{raw}\"")).ok()
+    }
+
+    fn skip_value(&mut self) -> Option<()> {
+        self.skip_whitespace();
+        match self.peek()? {
+            b'"' => self.string().map(|_| ()),
+            b'{' | b'[' => {
+                let mut depth = 0usize;
+                loop {
+                    match self.peek()? {
+                        b'"' => {
+                            self.string()?;
+                            continue;
+                        }
+                        b'{' | b'[' => depth += 1,
+                        b'}' | b']' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                self.offset += 1;
+                                return Some(());
+                            }
+                        }
+                        _ => {}
+                    }
+                    self.offset += 1;
+                }
+            }
+            _ => {
+                while !matches!(self.peek(), None | Some(b',' | b'}' | b']')) {
+                    self.offset += 1;
+                }
+                Some(())
+            }
+        }
+    }
+
+    fn enter(&mut self, segment: &PathSegment) -> Option<()> {
+        match segment {
+            PathSegment::Key(key) => {
+                self.expect(b'{')?;
+                loop {
+                    let name = self.string()?;
+                    self.expect(b':')?;
+                    if name == *key {
+                        return Some(());
+                    }
+                    self.skip_value()?;
+                    self.expect(b',')?;
+                }
+            }
+            PathSegment::Index(index) => {
+                self.expect(b'[')?;
+                for _ in 0..*index {
+                    self.skip_value()?;
+                    self.expect(b',')?;
+                }
+                Some(())
+            }
+        }
+    }
+}
+
+/// Finds the 1-based line and column of the value at `path` in a JSON document
+pub fn locate(source: &str, path: &[PathSegment]) -> Option<(usize, usize)> {
+    let mut scanner = Scanner {
+        source: source.as_bytes(),
+        offset: 0,
+    };
+
+    for segment in path {
+        scanner.enter(segment)?;
+    }
+    scanner.skip_whitespace();
+
+    let before = &source[..scanner.offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rsplit('\n')
+        .next()
+        .map(|line| line.chars().count())
+        .unwrap_or_default()
+        + 1;
+
+    Some((line, column))
+}
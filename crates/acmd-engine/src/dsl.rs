@@ -0,0 +1,814 @@
+//! A Lua-like text syntax for scripts, which is easier to write by hand than JSON
+//!
+//! ```text
+//! -- Scripts start with their header fields
+//! agent = "mario"
+//! category = Game
+//! replace = "game_attack11"
+//!
+//! frame(5)
+//! WorkModule.on_flag(FIGHTER_STATUS_ATTACK_FLAG_ENABLE_COMBO)
+//! ATTACK(id = 0, part = 0, bone = "top", damage = 2.2, angle = Normal(361), ...)
+//! If(condition = IsExcute) then
+//!     AttackModule.clear_all()
+//! else
+//!     wait(1)
+//! end
+//! ```
+//!
+//! Every statement is an action call, written with either its registered name or its ACMD name
+//! (see [`Action::ACMD_NAME`](crate::action::Action::ACMD_NAME)). The arguments are converted to
+//! the same JSON value that the action uses in `.acmd` files:
+//! - no arguments is a bare action, like `"Attack.clear_all"`
+//! - a single argument is the action's value, `frame(5)` is `{ "WaitUntil": 5 }`
+//! - several arguments are an array, for tuple actions
+//! - named arguments are a map of the action's fields
+//!
+//! Control actions take named arguments and their blocks follow the call: `then` and `else` become
+//! the `then` and `else` fields, and `do` becomes the `actions` field. The blocks are closed by `end`.
+//!
+//! Templates are used like any other action, as `Template(name = "jab_hitbox", with = { damage = 3 })`,
+//! see [`template`](crate::template).
+//!
+//! Values are numbers, strings, `true`/`false`, `nil`, tables (`{1, 2}` for arrays and
+//! `{ x = 1 }` for maps, with `{=}` as the empty map), bare identifiers, which are strings, and
+//! calls like `Normal(361)`, which are single key maps.
+
+use std::fmt::Write;
+
+use hash40::Hash40;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Number, Value};
+use thiserror::Error;
+
+use crate::{
+    action::ActionRegistry,
+    asset::{LatestSmashlineScript, ScriptMetadata},
+    labels,
+    template::TEMPLATE_ACTION,
+};
+
+#[derive(Error, Debug)]
+#[error("line {line}, column {column}: {message}")]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+const BLOCK_KEYWORDS: &[(&str, &str)] = &[("then", "then"), ("else", "else"), ("do", "actions")];
+const KEYWORDS: &[&str] = &["then", "else", "do", "end", "true", "false", "nil"];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(String),
+    String(String),
+    LParen,
+    RParen,
+    LBrace,
+    RBrace,
+    LBracket,
+    RBracket,
+    Comma,
+    Dot,
+    Equals,
+    Eof,
+}
+
+struct Lexer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError {
+            line: self.line,
+            column: self.column,
+            message: message.into(),
+        }
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_trivia(&mut self) {
+        loop {
+            match self.chars.peek() {
+                Some(c) if c.is_whitespace() => {
+                    self.bump();
+                }
+                Some('-') => {
+                    let mut lookahead = self.chars.clone();
+                    lookahead.next();
+                    if lookahead.next() != Some('-') {
+                        return;
+                    }
+                    while !matches!(self.chars.peek(), None | Some('\n')) {
+                        self.bump();
+                    }
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn next(&mut self) -> Result<(Token, usize, usize), ParseError> {
+        self.skip_trivia();
+        let (line, column) = (self.line, self.column);
+
+        let Some(c) = self.bump() else {
+            return Ok((Token::Eof, line, column));
+        };
+
+        let token = match c {
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '{' => Token::LBrace,
+            '}' => Token::RBrace,
+            '[' => Token::LBracket,
+            ']' => Token::RBracket,
+            ',' => Token::Comma,
+            '.' => Token::Dot,
+            '=' => Token::Equals,
+            '"' | '\'' => {
+                let mut string = String::new();
+                loop {
+                    match self.bump() {
+                        None | Some('\n') => return Err(self.error("unterminated string")),
+                        Some(end) if end == c => break,
+                        Some('\\') => match self.bump() {
+                            Some('n') => string.push('\n'),
+                            Some('t') => string.push('\t'),
+                            Some('r') => string.push('\r'),
+                            Some(escaped @ ('\\' | '"' | '\'')) => string.push(escaped),
+                            _ => return Err(self.error("invalid escape sequence")),
+                        },
+                        Some(c) => string.push(c),
+                    }
+                }
+                Token::String(string)
+            }
+            c if c == '-' || c.is_ascii_digit() => {
+                let mut number = c.to_string();
+                while let Some(&c) = self.chars.peek() {
+                    let exponent_sign = (c == '-' || c == '+') && number.ends_with(['e', 'E']);
+                    if c.is_ascii_alphanumeric() || c == '.' || exponent_sign {
+                        number.push(c);
+                        self.bump();
+                    } else {
+                        break;
+                    }
+                }
+                Token::Number(number)
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut ident = c.to_string();
+                while let Some(&c) = self.chars.peek() {
+                    if c.is_alphanumeric() || c == '_' {
+                        ident.push(c);
+                        self.bump();
+                    } else {
+                        break;
+                    }
+                }
+                Token::Ident(ident)
+            }
+            c => return Err(self.error(format!("unexpected character '{c}'"))),
+        };
+
+        Ok((token, line, column))
+    }
+}
+
+struct Parser<'a> {
+    lexer: Lexer<'a>,
+    token: Token,
+    line: usize,
+    column: usize,
+}
+
+enum Args {
+    Positional(Vec<Value>),
+    Named(Map<String, Value>),
+}
+
+impl<'a> Parser<'a> {
+    fn new(source: &'a str) -> Result<Self, ParseError> {
+        let mut lexer = Lexer {
+            chars: source.chars().peekable(),
+            line: 1,
+            column: 1,
+        };
+        let (token, line, column) = lexer.next()?;
+        Ok(Self {
+            lexer,
+            token,
+            line,
+            column,
+        })
+    }
+
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError {
+            line: self.line,
+            column: self.column,
+            message: message.into(),
+        }
+    }
+
+    fn advance(&mut self) -> Result<Token, ParseError> {
+        let (token, line, column) = self.lexer.next()?;
+        self.line = line;
+        self.column = column;
+        Ok(std::mem::replace(&mut self.token, token))
+    }
+
+    fn expect(&mut self, token: Token) -> Result<(), ParseError> {
+        if self.token != token {
+            return Err(self.error(format!("expected {token:?}, found {:?}", self.token)));
+        }
+        self.advance().map(|_| ())
+    }
+
+    /// Whether the current identifier is followed by `=`, making it a key instead of a value
+    fn is_key(&self) -> bool {
+        matches!(&self.token, Token::Ident(_))
+            && self.lexer.chars.clone().find(|c| !c.is_whitespace()) == Some('=')
+    }
+
+    /// Whether the current token closes the block being parsed
+    fn is_block_end(&self) -> bool {
+        self.is_keyword("end")
+            || BLOCK_KEYWORDS
+                .iter()
+                .any(|(keyword, _)| self.is_keyword(keyword))
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(&self.token, Token::Ident(ident) if ident == keyword)
+    }
+
+    fn ident(&mut self) -> Result<String, ParseError> {
+        match self.advance()? {
+            Token::Ident(ident) => Ok(ident),
+            token => Err(self.error(format!("expected an identifier, found {token:?}"))),
+        }
+    }
+
+    /// A possibly dotted name, like `WorkModule.on_flag`
+    fn name(&mut self) -> Result<String, ParseError> {
+        let mut name = self.ident()?;
+        while self.token == Token::Dot {
+            self.advance()?;
+            name.push('.');
+            name.push_str(&self.ident()?);
+        }
+        Ok(name)
+    }
+
+    fn number(&self, number: &str) -> Result<Value, ParseError> {
+        // Checked before the floats, since hex digits include 'e' and 'E'
+        let value = if let Some(hex) = number.strip_prefix("0x") {
+            u64::from_str_radix(hex, 16).ok().map(Value::from)
+        } else if number.contains(['.', 'e', 'E']) {
+            number
+                .parse::<f64>()
+                .ok()
+                .and_then(Number::from_f64)
+                .map(Value::Number)
+        } else if number.starts_with('-') {
+            number.parse::<i64>().ok().map(Value::from)
+        } else {
+            number.parse::<u64>().ok().map(Value::from)
+        };
+
+        value.ok_or_else(|| self.error(format!("invalid number '{number}'")))
+    }
+
+    fn args(&mut self) -> Result<Args, ParseError> {
+        let (line, column) = (self.line, self.column);
+        self.expect(Token::LParen)?;
+
+        let mut positional = vec![];
+        let mut named = Map::new();
+
+        while self.token != Token::RParen {
+            if self.is_key() {
+                let key = self.ident()?;
+                self.expect(Token::Equals)?;
+                named.insert(key, self.value()?);
+            } else {
+                positional.push(self.value()?);
+            }
+
+            if self.token != Token::Comma {
+                break;
+            }
+            self.advance()?;
+        }
+        self.expect(Token::RParen)?;
+
+        match (positional.is_empty(), named.is_empty()) {
+            (_, true) => Ok(Args::Positional(positional)),
+            (true, false) => Ok(Args::Named(named)),
+            (false, false) => Err(ParseError {
+                line,
+                column,
+                message: "positional and named arguments can't be mixed".to_string(),
+            }),
+        }
+    }
+
+    fn args_value(args: Args) -> Value {
+        match args {
+            Args::Named(map) => Value::Object(map),
+            Args::Positional(mut values) if values.len() == 1 => values.pop().unwrap(),
+            Args::Positional(values) if values.is_empty() => Value::Null,
+            Args::Positional(values) => Value::Array(values),
+        }
+    }
+
+    fn table(&mut self) -> Result<Value, ParseError> {
+        self.expect(Token::LBrace)?;
+
+        // `{}` is an empty array, so an empty map is written as `{=}`
+        if self.token == Token::Equals {
+            self.advance()?;
+            self.expect(Token::RBrace)?;
+            return Ok(Value::Object(Map::new()));
+        }
+
+        let mut array = vec![];
+        let mut map = Map::new();
+
+        while self.token != Token::RBrace {
+            if self.is_key() {
+                let key = self.ident()?;
+                self.expect(Token::Equals)?;
+                map.insert(key, self.value()?);
+            } else if self.token == Token::LBracket {
+                self.advance()?;
+                let Token::String(key) = self.advance()? else {
+                    return Err(self.error("table keys in brackets must be strings"));
+                };
+                self.expect(Token::RBracket)?;
+                self.expect(Token::Equals)?;
+                map.insert(key, self.value()?);
+            } else {
+                array.push(self.value()?);
+            }
+
+            if self.token != Token::Comma {
+                break;
+            }
+            self.advance()?;
+        }
+        self.expect(Token::RBrace)?;
+
+        match (array.is_empty(), map.is_empty()) {
+            (_, true) => Ok(Value::Array(array)),
+            (true, false) => Ok(Value::Object(map)),
+            (false, false) => Err(self.error("tables can't mix array and map entries")),
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, ParseError> {
+        match &self.token {
+            Token::Number(number) => {
+                let value = self.number(number)?;
+                self.advance()?;
+                Ok(value)
+            }
+            Token::String(_) => match self.advance()? {
+                Token::String(string) => Ok(Value::String(string)),
+                _ => unreachable!(),
+            },
+            Token::LBrace => self.table(),
+            Token::Ident(ident) if ident == "true" || ident == "false" => {
+                let value = ident == "true";
+                self.advance()?;
+                Ok(Value::Bool(value))
+            }
+            Token::Ident(ident) if ident == "nil" => {
+                self.advance()?;
+                Ok(Value::Null)
+            }
+            Token::Ident(_) => {
+                let name = self.name()?;
+                if self.token != Token::LParen {
+                    return Ok(Value::String(name));
+                }
+
+                let value = Self::args_value(self.args()?);
+                let mut map = Map::new();
+                map.insert(name, value);
+                Ok(Value::Object(map))
+            }
+            token => Err(self.error(format!("expected a value, found {token:?}"))),
+        }
+    }
+
+    fn action(&mut self, registry: &ActionRegistry) -> Result<Value, ParseError> {
+        let (line, column) = (self.line, self.column);
+        let name = self.name()?;
+        // Templates are expanded before the actions are loaded, so they are never registered
+        let resolved = if name == TEMPLATE_ACTION {
+            Some(name.clone())
+        } else {
+            registry.resolve_name(&name)
+        };
+        let Some(name) = resolved else {
+            return Err(ParseError {
+                line,
+                column,
+                message: format!("'{name}' is not a registered action"),
+            });
+        };
+
+        let args = self.args()?;
+
+        // Only `then` and `do` open the blocks, an `else` after a plain call belongs to the
+        // enclosing action
+        let mut blocks = Map::new();
+        let opens_block = self.is_keyword("then") || self.is_keyword("do");
+        while let Some((keyword, field)) = BLOCK_KEYWORDS
+            .iter()
+            .find(|(keyword, _)| opens_block && self.is_keyword(keyword))
+        {
+            if blocks.contains_key(*field) {
+                return Err(self.error(format!("'{keyword}' can only be used once")));
+            }
+            self.advance()?;
+            blocks.insert(field.to_string(), Value::Array(self.block(registry)?));
+        }
+
+        let value = if blocks.is_empty() {
+            Self::args_value(args)
+        } else {
+            self.expect(Token::Ident("end".to_string()))?;
+            match args {
+                Args::Named(mut map) => {
+                    map.extend(blocks);
+                    Value::Object(map)
+                }
+                Args::Positional(values) if values.is_empty() => Value::Object(blocks),
+                Args::Positional(_) => {
+                    return Err(self.error("actions with blocks only take named arguments"))
+                }
+            }
+        };
+
+        if value.is_null() {
+            return Ok(Value::String(name));
+        }
+
+        let mut map = Map::new();
+        map.insert(name, value);
+        Ok(Value::Object(map))
+    }
+
+    fn block(&mut self, registry: &ActionRegistry) -> Result<Vec<Value>, ParseError> {
+        let mut actions = vec![];
+        while !self.is_block_end() {
+            if self.token == Token::Eof {
+                return Err(self.error("expected 'end'"));
+            }
+            actions.push(self.action(registry)?);
+        }
+        Ok(actions)
+    }
+}
+
+fn header_field<T: DeserializeOwned>(
+    parser: &Parser,
+    key: &str,
+    value: Value,
+) -> Result<T, ParseError> {
+    serde_json::from_value(value).map_err(|e| parser.error(format!("invalid '{key}': {e}")))
+}
+
+fn header_hash(parser: &Parser, key: &str, value: Value) -> Result<Hash40, ParseError> {
+    labels::hash::deserialize(value).map_err(|e| parser.error(format!("invalid '{key}': {e}")))
+}
+
+/// Parses the text syntax into the latest script format, with its actions still in their JSON form
+pub fn parse(registry: &ActionRegistry, source: &str) -> Result<LatestSmashlineScript, ParseError> {
+    parse_with_positions(registry, source).map(|(script, _)| script)
+}
+
+/// Same as [`parse`], along with the line and column of every top level action
+pub(crate) fn parse_with_positions(
+    registry: &ActionRegistry,
+    source: &str,
+) -> Result<(LatestSmashlineScript, Vec<(usize, usize)>), ParseError> {
+    let mut parser = Parser::new(source)?;
+
+    let mut agent = None;
+    let mut category = None;
+    let mut replace = None;
+    let mut costumes = vec![];
+    let mut metadata = ScriptMetadata::default();
+
+    // Header fields are assignments, which are told apart from calls by the '=' after the name
+    while parser.is_key() {
+        let key = parser.ident()?;
+        parser.expect(Token::Equals)?;
+        let value = parser.value()?;
+        match key.as_str() {
+            "agent" => agent = Some(header_hash(&parser, &key, value)?),
+            "category" => category = Some(header_field(&parser, &key, value)?),
+            "replace" => replace = Some(header_hash(&parser, &key, value)?),
+            "costumes" => costumes = header_field(&parser, &key, value)?,
+            "metadata" => metadata = header_field(&parser, &key, value)?,
+            _ => return Err(parser.error(format!("unknown header field '{key}'"))),
+        }
+    }
+
+    let mut actions = vec![];
+    let mut positions = vec![];
+    while parser.token != Token::Eof {
+        positions.push((parser.line, parser.column));
+        actions.push(parser.action(registry)?);
+    }
+
+    let missing = |field: &str| parser.error(format!("missing header field '{field}'"));
+
+    let script = LatestSmashlineScript {
+        agent: agent.ok_or_else(|| missing("agent"))?,
+        category: category.ok_or_else(|| missing("category"))?,
+        replace: replace.ok_or_else(|| missing("replace"))?,
+        costumes,
+        metadata,
+        actions,
+    };
+
+    Ok((script, positions))
+}
+
+fn is_identifier(string: &str) -> bool {
+    let mut chars = string.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+        && !KEYWORDS.contains(&string)
+}
+
+fn write_string(out: &mut String, string: &str) {
+    out.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn write_args(out: &mut String, value: &Value) {
+    out.push('(');
+    match value {
+        Value::Null => {}
+        Value::Object(map) if is_named(map) => {
+            for (index, (key, value)) in map.iter().enumerate() {
+                if index != 0 {
+                    out.push_str(", ");
+                }
+                let _ = write!(out, "{key} = ");
+                write_value(out, value);
+            }
+        }
+        Value::Array(values) if values.len() > 1 => {
+            for (index, value) in values.iter().enumerate() {
+                if index != 0 {
+                    out.push_str(", ");
+                }
+                write_value(out, value);
+            }
+        }
+        value => write_value(out, value),
+    }
+    out.push(')');
+}
+
+fn write_value(out: &mut String, value: &Value) {
+    match value {
+        Value::Null => out.push_str("nil"),
+        Value::Bool(value) => {
+            let _ = write!(out, "{value}");
+        }
+        Value::Number(number) => {
+            let _ = write!(out, "{number}");
+        }
+        Value::String(string) if is_identifier(string) => out.push_str(string),
+        Value::String(string) => write_string(out, string),
+        Value::Array(values) => {
+            out.push('{');
+            for (index, value) in values.iter().enumerate() {
+                if index != 0 {
+                    out.push_str(", ");
+                }
+                write_value(out, value);
+            }
+            out.push('}');
+        }
+        Value::Object(map) if map.len() == 1 => {
+            let (key, inner) = map.iter().next().unwrap();
+            if is_identifier(key) && !inner.is_null() {
+                out.push_str(key);
+                write_args(out, inner);
+            } else {
+                write_table(out, map);
+            }
+        }
+        Value::Object(map) => write_table(out, map),
+    }
+}
+
+fn write_table(out: &mut String, map: &Map<String, Value>) {
+    if map.is_empty() {
+        out.push_str("{=}");
+        return;
+    }
+
+    out.push('{');
+    for (index, (key, value)) in map.iter().enumerate() {
+        if index != 0 {
+            out.push_str(", ");
+        }
+        if is_identifier(key) {
+            out.push_str(key);
+        } else {
+            out.push('[');
+            write_string(out, key);
+            out.push(']');
+        }
+        out.push_str(" = ");
+        write_value(out, value);
+    }
+    out.push('}');
+}
+
+const MAX_LINE_WIDTH: usize = 100;
+
+struct Printer<'a> {
+    registry: &'a ActionRegistry,
+    out: String,
+}
+
+impl<'a> Printer<'a> {
+    fn indent(&mut self, depth: usize) {
+        for _ in 0..depth {
+            self.out.push_str("    ");
+        }
+    }
+
+    fn action(&mut self, value: &Value, depth: usize) {
+        let (name, value) = match value {
+            Value::String(name) => (name.as_str(), &Value::Null),
+            Value::Object(map) if map.len() == 1 => {
+                let (name, value) = map.iter().next().unwrap();
+                (name.as_str(), value)
+            }
+            // Scripts that were loaded can't have invalid actions, but write them out as a comment
+            other => {
+                self.indent(depth);
+                let _ = writeln!(self.out, "-- invalid action: {other}");
+                return;
+            }
+        };
+
+        self.indent(depth);
+        let acmd_name = self
+            .registry
+            .acmd_name(name)
+            .unwrap_or_else(|| name.to_string());
+        self.out.push_str(&acmd_name);
+
+        let mut args = value.clone();
+        let mut blocks = vec![];
+        if self.registry.is_control(name) {
+            if let Value::Object(map) = &mut args {
+                for (keyword, field) in BLOCK_KEYWORDS {
+                    if let Some(Value::Array(actions)) = map.remove(*field) {
+                        blocks.push((*keyword, actions));
+                    }
+                }
+                if map.is_empty() {
+                    args = Value::Null;
+                }
+            }
+        }
+
+        let mut line = String::new();
+        write_args(&mut line, &args);
+
+        let width = depth * 4 + acmd_name.len() + line.len();
+        match &args {
+            Value::Object(map) if width > MAX_LINE_WIDTH && is_named(map) => {
+                self.out.push_str("(\n");
+                for (key, value) in map.iter() {
+                    self.indent(depth + 1);
+                    let _ = write!(self.out, "{key} = ");
+                    write_value(&mut self.out, value);
+                    self.out.push_str(",\n");
+                }
+                self.indent(depth);
+                self.out.push(')');
+            }
+            _ => self.out.push_str(&line),
+        }
+
+        if blocks.is_empty() {
+            self.out.push('\n');
+            return;
+        }
+
+        for (index, (keyword, actions)) in blocks.into_iter().enumerate() {
+            // The first block opens on the line of the call, the others get a line of their own
+            if index == 0 {
+                let _ = writeln!(self.out, " {keyword}");
+            } else {
+                self.indent(depth);
+                let _ = writeln!(self.out, "{keyword}");
+            }
+
+            for action in actions.iter() {
+                self.action(action, depth + 1);
+            }
+        }
+        self.indent(depth);
+        self.out.push_str("end\n");
+    }
+}
+
+fn is_named(map: &Map<String, Value>) -> bool {
+    !map.is_empty() && map.keys().all(|key| is_identifier(key))
+}
+
+/// Writes a script, in the latest format, as text
+pub fn print(registry: &ActionRegistry, script: &LatestSmashlineScript) -> String {
+    let mut printer = Printer {
+        registry,
+        out: String::new(),
+    };
+
+    let header = [
+        (
+            "agent",
+            Some(Value::String(labels::to_string(script.agent))),
+        ),
+        ("category", serde_json::to_value(script.category).ok()),
+        (
+            "replace",
+            Some(Value::String(labels::to_string(script.replace))),
+        ),
+        (
+            "costumes",
+            (!script.costumes.is_empty()).then(|| Value::from(script.costumes.clone())),
+        ),
+        (
+            "metadata",
+            (!script.metadata.is_empty())
+                .then(|| serde_json::to_value(&script.metadata).ok())
+                .flatten(),
+        ),
+    ];
+
+    for (key, value) in header {
+        if let Some(value) = value {
+            let _ = write!(printer.out, "{key} = ");
+            match &value {
+                // Hashes are always quoted, even when their label would be a valid identifier
+                Value::String(string) if key != "category" => {
+                    write_string(&mut printer.out, string)
+                }
+                value => write_value(&mut printer.out, value),
+            }
+            printer.out.push('\n');
+        }
+    }
+
+    if !script.actions.is_empty() {
+        printer.out.push('\n');
+    }
+
+    for action in script.actions.iter() {
+        printer.action(action, 0);
+    }
+
+    printer.out
+}
@@ -0,0 +1,703 @@
+//! Arithmetic expressions for the number fields of actions
+//!
+//! A field of type [`Expr`] takes either a plain number or a string holding an expression:
+//!
+//! ```json
+//! { "Attack.set": { "damage": "12.0 * 1.2", "radius": "max(work_float(FIGHTER_INSTANCE_WORK_ID_FLOAT_CHARGE) * 2, 4)", ... } }
+//! ```
+//!
+//! Expressions are made of numbers, `+`, `-`, `*`, `/`, parentheses, lua constants by name and the
+//! functions below. The `work_*` functions read the agent's `WorkModule` every time the action
+//! runs, everything else is folded into a constant when the script is loaded, so `"12.0 * 1.2"`
+//! costs the same as `14.4`.
+//!
+//! | Function | Value |
+//! | --- | --- |
+//! | `work_float(NAME)` | The float `NAME` of the agent's `WorkModule` |
+//! | `work_int(NAME)` | The int `NAME` of the agent's `WorkModule` |
+//! | `work_flag(NAME)` | `1` when the flag `NAME` of the agent's `WorkModule` is on, `0` otherwise |
+//! | `min(a, b)`, `max(a, b)` | The smaller or the larger of two values |
+//! | `clamp(x, min, max)` | `x` limited to the range from `min` to `max` |
+//! | `abs(x)`, `floor(x)`, `ceil(x)`, `round(x)` | The same as the `f32` methods of the same name |
+
+use std::fmt::Display;
+
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{InstanceType, Schema, SchemaObject},
+    JsonSchema,
+};
+use serde::{de::Visitor, Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    codegen,
+    context::{self, ExecutionContext},
+};
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ExprError {
+    #[error("Unexpected end of expression")]
+    UnexpectedEnd,
+
+    #[error("Unexpected '{token}' at column {column}")]
+    UnexpectedToken { token: String, column: usize },
+
+    #[error("Unknown function '{0}'")]
+    UnknownFunction(String),
+
+    #[error("'{function}' takes {expected} arguments, found {found}")]
+    ArgumentCount {
+        function: &'static str,
+        expected: usize,
+        found: usize,
+    },
+
+    #[error("'{function}' takes the name of a lua constant")]
+    ExpectedConstant { function: &'static str },
+
+    #[error("Unknown lua constant '{0}'")]
+    UnknownConstant(String),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl BinaryOp {
+    fn apply(self, lhs: f32, rhs: f32) -> f32 {
+        match self {
+            Self::Add => lhs + rhs,
+            Self::Sub => lhs - rhs,
+            Self::Mul => lhs * rhs,
+            Self::Div => lhs / rhs,
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::Mul => "*",
+            Self::Div => "/",
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Function {
+    Min,
+    Max,
+    Clamp,
+    Abs,
+    Floor,
+    Ceil,
+    Round,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "min" => Some(Self::Min),
+            "max" => Some(Self::Max),
+            "clamp" => Some(Self::Clamp),
+            "abs" => Some(Self::Abs),
+            "floor" => Some(Self::Floor),
+            "ceil" => Some(Self::Ceil),
+            "round" => Some(Self::Round),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Min => "min",
+            Self::Max => "max",
+            Self::Clamp => "clamp",
+            Self::Abs => "abs",
+            Self::Floor => "floor",
+            Self::Ceil => "ceil",
+            Self::Round => "round",
+        }
+    }
+
+    fn arity(self) -> usize {
+        match self {
+            Self::Min | Self::Max => 2,
+            Self::Clamp => 3,
+            Self::Abs | Self::Floor | Self::Ceil | Self::Round => 1,
+        }
+    }
+
+    fn apply(self, args: &[f32]) -> f32 {
+        match self {
+            Self::Min => args[0].min(args[1]),
+            Self::Max => args[0].max(args[1]),
+            // Unlike `f32::clamp`, this doesn't panic when the range is backwards
+            Self::Clamp => args[0].max(args[1]).min(args[2]),
+            Self::Abs => args[0].abs(),
+            Self::Floor => args[0].floor(),
+            Self::Ceil => args[0].ceil(),
+            Self::Round => args[0].round(),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum WorkKind {
+    Float,
+    Int,
+    Flag,
+}
+
+impl WorkKind {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "work_float" => Some(Self::Float),
+            "work_int" => Some(Self::Int),
+            "work_flag" => Some(Self::Flag),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Float => "work_float",
+            Self::Int => "work_int",
+            Self::Flag => "work_flag",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Const(f32),
+    /// A lua constant by name, resolved when the expression is parsed
+    LuaConst {
+        name: String,
+        value: i32,
+    },
+    Work {
+        kind: WorkKind,
+        name: String,
+        value: i32,
+    },
+    Neg(Box<Node>),
+    Binary(BinaryOp, Box<Node>, Box<Node>),
+    Call(Function, Vec<Node>),
+}
+
+impl Node {
+    // The constructors fold their operands when they are constant, so parsing folds bottom up
+
+    fn neg(node: Node) -> Self {
+        match node {
+            Self::Const(value) => Self::Const(-value),
+            node => Self::Neg(Box::new(node)),
+        }
+    }
+
+    fn binary(op: BinaryOp, lhs: Node, rhs: Node) -> Self {
+        match (lhs, rhs) {
+            (Self::Const(lhs), Self::Const(rhs)) => Self::Const(op.apply(lhs, rhs)),
+            (lhs, rhs) => Self::Binary(op, Box::new(lhs), Box::new(rhs)),
+        }
+    }
+
+    fn call(function: Function, args: Vec<Node>) -> Self {
+        let constants = args
+            .iter()
+            .map(|arg| match arg {
+                Self::Const(value) => Some(*value),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>();
+
+        match constants {
+            Some(values) => Self::Const(function.apply(&values)),
+            None => Self::Call(function, args),
+        }
+    }
+
+    fn evaluate(&self, context: &mut dyn ExecutionContext) -> f32 {
+        match self {
+            Self::Const(value) => *value,
+            Self::LuaConst { value, .. } => *value as f32,
+            Self::Work { kind, value, .. } => match kind {
+                WorkKind::Float => context.get_float(*value),
+                WorkKind::Int => context.get_int(*value) as f32,
+                WorkKind::Flag => context.is_flag(*value) as i32 as f32,
+            },
+            Self::Neg(node) => -node.evaluate(context),
+            Self::Binary(op, lhs, rhs) => op.apply(lhs.evaluate(context), rhs.evaluate(context)),
+            Self::Call(function, args) => {
+                let values = args
+                    .iter()
+                    .map(|arg| arg.evaluate(context))
+                    .collect::<Vec<_>>();
+                function.apply(&values)
+            }
+        }
+    }
+
+    /// Whether the Rust for the node can be used as an operand or method receiver as it is
+    fn is_atom(&self) -> bool {
+        match self {
+            Self::Const(value) => value.is_sign_positive() && value.is_finite(),
+            Self::LuaConst { .. } | Self::Work { .. } | Self::Call(..) => true,
+            Self::Neg(_) | Self::Binary(..) => false,
+        }
+    }
+
+    fn to_rust_operand(&self) -> String {
+        if self.is_atom() {
+            self.to_rust()
+        } else {
+            format!("({})", self.to_rust())
+        }
+    }
+
+    fn to_rust(&self) -> String {
+        match self {
+            Self::Const(value) => codegen::float(*value),
+            Self::LuaConst { name, .. } => format!("(*{name} as f32)"),
+            Self::Work { kind, name, .. } => match kind {
+                WorkKind::Float => format!("WorkModule::get_float(agent.module_accessor, *{name})"),
+                WorkKind::Int => {
+                    format!("(WorkModule::get_int(agent.module_accessor, *{name}) as f32)")
+                }
+                WorkKind::Flag => {
+                    format!("(WorkModule::is_flag(agent.module_accessor, *{name}) as i32 as f32)")
+                }
+            },
+            Self::Neg(node) => format!("-{}", node.to_rust_operand()),
+            Self::Binary(op, lhs, rhs) => format!(
+                "{} {} {}",
+                lhs.to_rust_operand(),
+                op.symbol(),
+                rhs.to_rust_operand()
+            ),
+            Self::Call(function, args) => {
+                let receiver = args[0].to_rust_operand();
+                let args = args[1..].iter().map(Node::to_rust).collect::<Vec<_>>();
+                match function {
+                    Function::Clamp => format!("{receiver}.max({}).min({})", args[0], args[1]),
+                    function => format!("{receiver}.{}({})", function.name(), args.join(", ")),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f32),
+    Ident(String),
+    Symbol(char),
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Number(value) => Display::fmt(value, f),
+            Self::Ident(name) => f.write_str(name),
+            Self::Symbol(symbol) => write!(f, "{symbol}"),
+        }
+    }
+}
+
+/// Splits an expression into tokens, with the column each one starts at
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, ExprError> {
+    let chars = source.chars().collect::<Vec<_>>();
+    let mut tokens = vec![];
+    let mut index = 0;
+
+    while index < chars.len() {
+        let start = index;
+        let char = chars[index];
+
+        if char.is_whitespace() {
+            index += 1;
+        } else if char.is_ascii_digit() || char == '.' {
+            while index < chars.len() && (chars[index].is_ascii_digit() || chars[index] == '.') {
+                index += 1;
+            }
+            // An exponent, such as the one in `1e-3`
+            if matches!(chars.get(index), Some('e' | 'E')) {
+                let digits = match chars.get(index + 1) {
+                    Some('+' | '-') => index + 2,
+                    _ => index + 1,
+                };
+                if chars.get(digits).is_some_and(char::is_ascii_digit) {
+                    index = digits;
+                    while index < chars.len() && chars[index].is_ascii_digit() {
+                        index += 1;
+                    }
+                }
+            }
+            let text = chars[start..index].iter().collect::<String>();
+            let value = text.parse().map_err(|_| ExprError::UnexpectedToken {
+                token: text.clone(),
+                column: start + 1,
+            })?;
+            tokens.push((start + 1, Token::Number(value)));
+        } else if char.is_ascii_alphabetic() || char == '_' {
+            while index < chars.len()
+                && (chars[index].is_ascii_alphanumeric() || chars[index] == '_')
+            {
+                index += 1;
+            }
+            let name = chars[start..index].iter().collect();
+            tokens.push((start + 1, Token::Ident(name)));
+        } else if "+-*/(),".contains(char) {
+            index += 1;
+            tokens.push((start + 1, Token::Symbol(char)));
+        } else {
+            return Err(ExprError::UnexpectedToken {
+                token: char.to_string(),
+                column: start + 1,
+            });
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    index: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index).map(|(_, token)| token)
+    }
+
+    fn next(&mut self) -> Result<(usize, Token), ExprError> {
+        let token = self
+            .tokens
+            .get(self.index)
+            .cloned()
+            .ok_or(ExprError::UnexpectedEnd)?;
+        self.index += 1;
+        Ok(token)
+    }
+
+    fn eat(&mut self, symbol: char) -> bool {
+        if self.peek() == Some(&Token::Symbol(symbol)) {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, symbol: char) -> Result<(), ExprError> {
+        match self.next()? {
+            (_, Token::Symbol(found)) if found == symbol => Ok(()),
+            (column, token) => Err(ExprError::UnexpectedToken {
+                token: token.to_string(),
+                column,
+            }),
+        }
+    }
+
+    fn finish(&mut self) -> Result<(), ExprError> {
+        match self.tokens.get(self.index) {
+            None => Ok(()),
+            Some((column, token)) => Err(ExprError::UnexpectedToken {
+                token: token.to_string(),
+                column: *column,
+            }),
+        }
+    }
+
+    /// `sum = product (("+" | "-") product)*`
+    fn sum(&mut self) -> Result<Node, ExprError> {
+        let mut node = self.product()?;
+        loop {
+            let op = if self.eat('+') {
+                BinaryOp::Add
+            } else if self.eat('-') {
+                BinaryOp::Sub
+            } else {
+                return Ok(node);
+            };
+            node = Node::binary(op, node, self.product()?);
+        }
+    }
+
+    /// `product = unary (("*" | "/") unary)*`
+    fn product(&mut self) -> Result<Node, ExprError> {
+        let mut node = self.unary()?;
+        loop {
+            let op = if self.eat('*') {
+                BinaryOp::Mul
+            } else if self.eat('/') {
+                BinaryOp::Div
+            } else {
+                return Ok(node);
+            };
+            node = Node::binary(op, node, self.unary()?);
+        }
+    }
+
+    /// `unary = "-" unary | primary`
+    fn unary(&mut self) -> Result<Node, ExprError> {
+        if self.eat('-') {
+            Ok(Node::neg(self.unary()?))
+        } else {
+            self.primary()
+        }
+    }
+
+    /// `primary = number | "(" sum ")" | name | name "(" (sum ("," sum)*)? ")"`
+    fn primary(&mut self) -> Result<Node, ExprError> {
+        match self.next()? {
+            (_, Token::Number(value)) => Ok(Node::Const(value)),
+            (_, Token::Symbol('(')) => {
+                let node = self.sum()?;
+                self.expect(')')?;
+                Ok(node)
+            }
+            (_, Token::Ident(name)) if self.eat('(') => self.call(name),
+            (_, Token::Ident(name)) => {
+                let value = lua_const(&name)?;
+                Ok(Node::LuaConst { name, value })
+            }
+            (column, token) => Err(ExprError::UnexpectedToken {
+                token: token.to_string(),
+                column,
+            }),
+        }
+    }
+
+    /// The arguments of a call, after its opening parenthesis
+    fn call(&mut self, name: String) -> Result<Node, ExprError> {
+        let mut args = vec![];
+        if !self.eat(')') {
+            loop {
+                args.push(self.sum()?);
+                if self.eat(')') {
+                    break;
+                }
+                self.expect(',')?;
+            }
+        }
+
+        if let Some(kind) = WorkKind::from_name(&name) {
+            return match args.as_slice() {
+                [Node::LuaConst { name, value }] => Ok(Node::Work {
+                    kind,
+                    name: name.clone(),
+                    value: *value,
+                }),
+                [_] => Err(ExprError::ExpectedConstant {
+                    function: kind.name(),
+                }),
+                _ => Err(ExprError::ArgumentCount {
+                    function: kind.name(),
+                    expected: 1,
+                    found: args.len(),
+                }),
+            };
+        }
+
+        let function = Function::from_name(&name).ok_or(ExprError::UnknownFunction(name))?;
+        if args.len() != function.arity() {
+            return Err(ExprError::ArgumentCount {
+                function: function.name(),
+                expected: function.arity(),
+                found: args.len(),
+            });
+        }
+
+        Ok(Node::call(function, args))
+    }
+}
+
+fn lua_const(name: &str) -> Result<i32, ExprError> {
+    context::lua_const(name).ok_or_else(|| ExprError::UnknownConstant(name.to_string()))
+}
+
+/// A number that is either constant or computed from the agent when the action runs, see the
+/// [module documentation](self)
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+#[cfg_attr(feature = "bevy_reflect", reflect_value(Debug, Default))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    /// The expression as it was written, kept so that saving the script doesn't fold it away
+    source: Option<String>,
+    node: Node,
+}
+
+impl Expr {
+    pub fn parse(source: impl Into<String>) -> Result<Self, ExprError> {
+        let source = source.into();
+        let mut parser = Parser {
+            tokens: tokenize(&source)?,
+            index: 0,
+        };
+        let node = parser.sum()?;
+        parser.finish()?;
+
+        Ok(Self {
+            source: Some(source),
+            node,
+        })
+    }
+
+    /// The value of the expression when it doesn't depend on the agent
+    pub fn constant(&self) -> Option<f32> {
+        match self.node {
+            Node::Const(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The expression as it was written, or `None` when it was written as a number
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+
+    /// Computes the value of the expression for the context that is running the action
+    pub fn evaluate(&self, context: &mut dyn ExecutionContext) -> f32 {
+        self.node.evaluate(context)
+    }
+
+    /// The expression as a Rust `f32` expression, with the agent bound as `agent`
+    pub fn to_rust(&self) -> String {
+        self.node.to_rust()
+    }
+}
+
+impl Default for Expr {
+    fn default() -> Self {
+        Self::from(0.0)
+    }
+}
+
+impl From<f32> for Expr {
+    fn from(value: f32) -> Self {
+        Self {
+            source: None,
+            node: Node::Const(value),
+        }
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.source, &self.node) {
+            (Some(source), _) => f.write_str(source),
+            (None, Node::Const(value)) => Display::fmt(value, f),
+            (None, node) => f.write_str(&node.to_rust()),
+        }
+    }
+}
+
+impl Serialize for Expr {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match (&self.source, &self.node) {
+            (Some(source), _) => serializer.serialize_str(source),
+            (None, Node::Const(value)) => serializer.serialize_f32(*value),
+            (None, node) => serializer.serialize_str(&node.to_rust()),
+        }
+    }
+}
+
+struct ExprVisitor;
+
+impl<'de> Visitor<'de> for ExprVisitor {
+    type Value = Expr;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a number or an expression")
+    }
+
+    fn visit_f64<E: serde::de::Error>(self, value: f64) -> Result<Expr, E> {
+        Ok(Expr::from(value as f32))
+    }
+
+    fn visit_i64<E: serde::de::Error>(self, value: i64) -> Result<Expr, E> {
+        Ok(Expr::from(value as f32))
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, value: u64) -> Result<Expr, E> {
+        Ok(Expr::from(value as f32))
+    }
+
+    fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Expr, E> {
+        Expr::parse(value)
+            .map_err(|error| E::custom(format!("invalid expression '{value}': {error}")))
+    }
+}
+
+impl<'de> Deserialize<'de> for Expr {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(ExprVisitor)
+    }
+}
+
+impl JsonSchema for Expr {
+    fn schema_name() -> String {
+        "Expr".to_string()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            instance_type: Some(vec![InstanceType::Number, InstanceType::String].into()),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::{set_lua_const, RecordingContext};
+
+    #[test]
+    fn exponents() {
+        assert_eq!(Expr::parse("1e-3").unwrap().constant(), Some(1e-3));
+        assert_eq!(Expr::parse("2.5E2 * 2").unwrap().constant(), Some(500.0));
+        assert_eq!(Expr::parse("1e+2 - 1").unwrap().constant(), Some(99.0));
+    }
+
+    #[test]
+    fn unknown_constant() {
+        assert_eq!(
+            Expr::parse("EXPR_TEST_NOT_SET * 2"),
+            Err(ExprError::UnknownConstant("EXPR_TEST_NOT_SET".to_string()))
+        );
+        assert_eq!(
+            Expr::parse("work_float(EXPR_TEST_NOT_SET)"),
+            Err(ExprError::UnknownConstant("EXPR_TEST_NOT_SET".to_string()))
+        );
+    }
+
+    #[test]
+    fn constants_and_work() {
+        set_lua_const("EXPR_TEST_CONST", 3);
+        set_lua_const("EXPR_TEST_FLOAT", 7);
+
+        let expr = Expr::parse("max(work_float(EXPR_TEST_FLOAT), EXPR_TEST_CONST) * 2").unwrap();
+        assert_eq!(expr.constant(), None);
+
+        let mut context = RecordingContext::new();
+        assert_eq!(expr.evaluate(&mut context), 6.0);
+        context.floats.insert(7, 5.0);
+        assert_eq!(expr.evaluate(&mut context), 10.0);
+    }
+}
@@ -0,0 +1,190 @@
+//! Labels for [`Hash40`] values, so that scripts are written with names like `game_attack11` instead
+//! of raw hashes
+//!
+//! Labels are read from a ParamLabels-style CSV, where every line is a hash and its label:
+//!
+//! ```text
+//! 0x0a4ed4bb3c,game_attack11
+//! 0x0b8afd1f0f,effect_attack11
+//! ```
+//!
+//! Smashline loads them from `sd:/ultimate/smashline/ParamLabels.csv` and shares them with every
+//! plugin. Once they are loaded with [`set_labels`], every hash that is serialized through [`hash`] is
+//! written as its label when it has one. Parsing accepts either form: `0x` followed by hex digits
+//! is the hash itself, and anything else is a label, which is hashed.
+
+use std::{
+    collections::BTreeMap,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+use hash40::Hash40;
+use serde::{de::Visitor, Deserializer, Serializer};
+use thiserror::Error;
+
+use crate::RwLock;
+
+#[derive(Error, Debug)]
+pub enum LabelError {
+    #[error("Line {line}: expected 'hash,label'")]
+    InvalidLine { line: usize },
+
+    #[error("Line {line}: invalid hash '{hash}'")]
+    InvalidHash { line: usize, hash: String },
+}
+
+/// A dictionary from hashes to the strings they were hashed from
+#[derive(Debug, Default, Clone)]
+pub struct Labels {
+    labels: BTreeMap<u64, String>,
+}
+
+impl Labels {
+    pub const fn new() -> Self {
+        Self {
+            labels: BTreeMap::new(),
+        }
+    }
+
+    /// Reads a ParamLabels-style CSV, skipping empty lines
+    pub fn from_csv(csv: &str) -> Result<Self, LabelError> {
+        let mut labels = Self::new();
+
+        for (index, line) in csv.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let Some((hash, label)) = line.split_once(',') else {
+                return Err(LabelError::InvalidLine { line: index + 1 });
+            };
+
+            let hash = parse_hex(hash.trim()).ok_or_else(|| LabelError::InvalidHash {
+                line: index + 1,
+                hash: hash.to_string(),
+            })?;
+
+            labels.labels.insert(hash, label.trim().to_string());
+        }
+
+        Ok(labels)
+    }
+
+    /// Adds a label, returning its hash
+    pub fn insert(&mut self, label: impl Into<String>) -> Hash40 {
+        let label = label.into();
+        let hash = Hash40::new(&label);
+        self.labels.insert(hash.0, label);
+        hash
+    }
+
+    pub fn get(&self, hash: Hash40) -> Option<&str> {
+        self.labels.get(&hash.0).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// Adds every label of `other`, replacing the labels of the same hashes
+    pub fn extend(&mut self, other: Labels) {
+        self.labels.extend(other.labels);
+    }
+}
+
+static LABELS: RwLock<Labels> = RwLock::new(Labels::new());
+
+/// The labels set with [`use_labels`], which this copy of the engine uses instead of [`LABELS`]
+static SHARED_LABELS: AtomicPtr<RwLock<Labels>> = AtomicPtr::new(std::ptr::null_mut());
+
+/// The labels that hashes are written with
+pub fn labels() -> &'static RwLock<Labels> {
+    let shared = SHARED_LABELS.load(Ordering::Acquire);
+    if shared.is_null() {
+        &LABELS
+    } else {
+        unsafe { &*shared }
+    }
+}
+
+/// Makes this copy of the engine use the labels of another one. Every plugin links its own copy of
+/// the engine, so plugins use the labels of smashline's copy, which it loads them into.
+pub fn use_labels(labels: &'static RwLock<Labels>) {
+    SHARED_LABELS.store(labels as *const _ as *mut _, Ordering::Release);
+}
+
+/// Replaces the labels that hashes are written with
+pub fn set_labels(new_labels: Labels) {
+    *labels().write() = new_labels;
+}
+
+/// Adds to the labels that hashes are written with, such as custom labels on top of ParamLabels
+pub fn add_labels(new_labels: Labels) {
+    labels().write().extend(new_labels);
+}
+
+/// The label of a hash, from the loaded labels or the labels of the `hash40` crate
+pub fn label_of(hash: Hash40) -> Option<String> {
+    if let Some(label) = labels().read().get(hash) {
+        return Some(label.to_string());
+    }
+
+    let string = hash.to_string();
+    (!string.starts_with("0x")).then_some(string)
+}
+
+/// The label of a hash, or the hash in hex when it has none
+pub fn to_string(hash: Hash40) -> String {
+    label_of(hash).unwrap_or_else(|| format!("{:#012x}", hash.0))
+}
+
+/// Reads a hash written as hex, like `0x0a4ed4bb3c`, or as a label
+pub fn parse(string: &str) -> Hash40 {
+    match parse_hex(string) {
+        Some(hash) => Hash40(hash),
+        None => Hash40::new(string),
+    }
+}
+
+fn parse_hex(string: &str) -> Option<u64> {
+    let hex = string
+        .strip_prefix("0x")
+        .or_else(|| string.strip_prefix("0X"))?;
+    u64::from_str_radix(hex, 16).ok()
+}
+
+/// Serializes a [`Hash40`] as its label, for `#[serde(with = "acmd_engine::labels::hash")]`
+pub mod hash {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(hash: &Hash40, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&to_string(*hash))
+    }
+
+    struct HashVisitor;
+
+    impl<'de> Visitor<'de> for HashVisitor {
+        type Value = Hash40;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("a label or a hash")
+        }
+
+        fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Hash40, E> {
+            Ok(parse(value))
+        }
+
+        fn visit_u64<E: serde::de::Error>(self, value: u64) -> Result<Hash40, E> {
+            Ok(Hash40(value))
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Hash40, D::Error> {
+        deserializer.deserialize_any(HashVisitor)
+    }
+}
@@ -1,42 +1,193 @@
+use std::sync::Arc;
+
 use action::{ActionError, ActionRegistry, DynamicAction};
-use asset::{Category, SmashlineScriptV0, VersionedSmashlineScript};
+use asset::{
+    Category, LatestScriptBundle, LatestScriptPatch, LatestScriptTemplate, LatestSmashlineScript,
+    ScriptMetadata, VersionedScriptBundle, VersionedScriptPatch, VersionedScriptTemplate,
+    VersionedSmashlineScript,
+};
+use diagnostic::{Diagnostics, PathSegment};
 use hash40::Hash40;
 use serde_json::Value;
+use template::Templates;
+use timeline::{Timeline, TimelineError};
 
 pub mod action;
 pub mod asset;
+pub mod binary;
+pub mod call;
+pub mod codegen;
+pub mod context;
+pub mod diagnostic;
+pub mod dsl;
+pub mod expr;
+pub mod labels;
+pub mod metadata;
+pub mod observer;
+pub mod patch;
+pub mod schema;
+pub mod template;
+pub mod timeline;
+
+#[cfg(not(feature = "parking_lot"))]
+pub type RwLock<T> = locks::RwLock<T>;
+
+#[cfg(feature = "parking_lot")]
+pub type RwLock<T> = parking_lot::RwLock<T>;
+
+/// A loaded script which can be swapped out in place when it is reloaded
+pub type ScriptHandle = Arc<RwLock<Arc<SmashlineScript>>>;
+
+/// Fills in the position of every invalid action from the JSON it was loaded from
+fn locate(error: ActionError, json: &str) -> ActionError {
+    match error {
+        ActionError::InvalidActions(mut diagnostics) => {
+            diagnostics.locate(json);
+            ActionError::InvalidActions(diagnostics)
+        }
+        error => error,
+    }
+}
 
 pub struct SmashlineScript {
     pub agent: Hash40,
     pub category: Category,
     pub replace: Hash40,
+    pub costumes: Vec<usize>,
+    pub metadata: ScriptMetadata,
     pub actions: Vec<DynamicAction>,
 }
 
 impl SmashlineScript {
-    fn from_v0(registry: &ActionRegistry, script: SmashlineScriptV0) -> Result<Self, ActionError> {
+    fn from_asset(
+        registry: &ActionRegistry,
+        templates: &Templates,
+        script: LatestSmashlineScript,
+    ) -> Result<Self, ActionError> {
+        let mut actions = Vec::with_capacity(script.actions.len());
+        let mut diagnostics = Diagnostics(vec![]);
+
+        for (index, action) in script.actions.into_iter().enumerate() {
+            match templates.load(registry, action) {
+                Ok(loaded) => actions.extend(loaded),
+                Err(error) => diagnostics.push(index, error),
+            }
+        }
+
+        if !diagnostics.0.is_empty() {
+            return Err(ActionError::InvalidActions(diagnostics));
+        }
+
         Ok(Self {
             agent: script.agent,
             category: script.category,
             replace: script.replace,
-            actions: script
-                .actions
-                .into_iter()
-                .map(|action| registry.as_action(action))
-                .collect::<Result<Vec<_>, _>>()?,
+            costumes: script.costumes,
+            metadata: script.metadata,
+            actions,
         })
     }
 
+    /// Loads every script of a bundle, reporting the invalid actions of all of them at once
+    fn from_bundle(
+        registry: &ActionRegistry,
+        templates: &Templates,
+        bundle: LatestScriptBundle,
+    ) -> Result<Vec<(String, Self)>, ActionError> {
+        let mut scripts = vec![];
+        let mut diagnostics = Diagnostics(vec![]);
+
+        for (name, script) in bundle.into_scripts() {
+            match Self::from_asset(registry, templates, script) {
+                Ok(script) => scripts.push((name, script)),
+                Err(ActionError::InvalidActions(entry)) => {
+                    let root = [
+                        PathSegment::Key("scripts".to_string()),
+                        PathSegment::Key(name),
+                    ];
+                    diagnostics.append_at(&root, entry);
+                }
+                Err(error) => return Err(error),
+            }
+        }
+
+        if !diagnostics.0.is_empty() {
+            return Err(ActionError::InvalidActions(diagnostics));
+        }
+
+        Ok(scripts)
+    }
+
+    fn from_versioned(
+        registry: &ActionRegistry,
+        templates: &Templates,
+        versioned: VersionedSmashlineScript,
+    ) -> Result<Self, ActionError> {
+        Self::from_asset(registry, templates, versioned.into_latest())
+    }
+
     pub fn from_json(
         registry: &ActionRegistry,
         json: impl AsRef<str>,
+    ) -> Result<Self, ActionError> {
+        Self::parse_json(registry, &Templates::default(), json.as_ref())
+    }
+
+    pub fn from_binary(
+        registry: &ActionRegistry,
+        data: impl AsRef<[u8]>,
+    ) -> Result<Self, ActionError> {
+        let value = binary::decode(data.as_ref()).map_err(ActionError::DecodeError)?;
+        Self::parse_value(registry, &Templates::default(), value)
+    }
+
+    /// Loads a script written in the text syntax, see [`dsl`]
+    pub fn from_text(
+        registry: &ActionRegistry,
+        source: impl AsRef<str>,
+    ) -> Result<Self, ActionError> {
+        Self::parse_text(registry, &Templates::default(), source.as_ref())
+    }
+
+    fn parse_json(
+        registry: &ActionRegistry,
+        templates: &Templates,
+        json: &str,
     ) -> Result<Self, ActionError> {
         let versioned: VersionedSmashlineScript =
-            serde_json::from_str(json.as_ref()).map_err(ActionError::ParseError)?;
+            serde_json::from_str(json).map_err(ActionError::ParseError)?;
 
-        match versioned {
-            VersionedSmashlineScript::V0(v0) => Self::from_v0(registry, v0),
-        }
+        Self::from_versioned(registry, templates, versioned).map_err(|error| locate(error, json))
+    }
+
+    fn parse_value(
+        registry: &ActionRegistry,
+        templates: &Templates,
+        value: Value,
+    ) -> Result<Self, ActionError> {
+        let versioned: VersionedSmashlineScript =
+            serde_json::from_value(value).map_err(ActionError::ParseError)?;
+
+        Self::from_versioned(registry, templates, versioned)
+    }
+
+    fn parse_text(
+        registry: &ActionRegistry,
+        templates: &Templates,
+        source: &str,
+    ) -> Result<Self, ActionError> {
+        let (script, positions) =
+            dsl::parse_with_positions(registry, source).map_err(ActionError::TextError)?;
+
+        Self::from_asset(registry, templates, script).map_err(|error| match error {
+            ActionError::InvalidActions(mut diagnostics) => {
+                for diagnostic in diagnostics.0.iter_mut() {
+                    diagnostic.position = positions.get(diagnostic.index).copied();
+                }
+                ActionError::InvalidActions(diagnostics)
+            }
+            error => error,
+        })
     }
 
     pub fn to_json(&self, registry: &ActionRegistry) -> Result<String, ActionError> {
@@ -48,4 +199,100 @@ impl SmashlineScript {
         let versioned = VersionedSmashlineScript::from_latest(registry, self)?;
         serde_json::to_value(versioned).map_err(ActionError::SerializeError)
     }
+
+    pub fn to_binary(&self, registry: &ActionRegistry) -> Result<Vec<u8>, ActionError> {
+        self.to_json_value(registry).map(|value| binary::encode(&value))
+    }
+
+    /// The frame that every action of the script runs on
+    pub fn timeline(
+        &self,
+        registry: &ActionRegistry,
+    ) -> Result<Timeline<&DynamicAction>, TimelineError> {
+        Timeline::from_actions(registry, self.actions.iter())
+    }
+
+    /// Generates the Rust source of an equivalent `#[smashline::acmd]` function
+    pub fn to_rust(&self, registry: &ActionRegistry) -> String {
+        codegen::script(registry, self)
+    }
+
+    pub fn to_text(&self, registry: &ActionRegistry) -> Result<String, ActionError> {
+        let latest = VersionedSmashlineScript::from_latest(registry, self)?.into_latest();
+        Ok(dsl::print(registry, &latest))
+    }
+}
+
+/// The contents of a script file, which either defines a script, bundles many scripts, patches the
+/// scripts of other files or defines a template for other files to instantiate
+pub enum ScriptFile {
+    Script(SmashlineScript),
+
+    /// The scripts of a bundle file, by the name of their entry
+    Bundle(Vec<(String, SmashlineScript)>),
+
+    Patch(LatestScriptPatch),
+    Template(LatestScriptTemplate),
+}
+
+impl ScriptFile {
+    /// Reads a file, instantiating the templates it uses from `templates`
+    pub fn from_json(
+        registry: &ActionRegistry,
+        templates: &Templates,
+        json: impl AsRef<str>,
+    ) -> Result<Self, ActionError> {
+        let json = json.as_ref();
+        let value = serde_json::from_str(json).map_err(ActionError::ParseError)?;
+        Self::from_value(registry, templates, value).map_err(|error| locate(error, json))
+    }
+
+    pub fn from_binary(
+        registry: &ActionRegistry,
+        templates: &Templates,
+        data: impl AsRef<[u8]>,
+    ) -> Result<Self, ActionError> {
+        let value = binary::decode(data.as_ref()).map_err(ActionError::DecodeError)?;
+        Self::from_value(registry, templates, value)
+    }
+
+    /// Tells the kind of file apart by its fields, so that the file is only parsed once
+    fn from_value(
+        registry: &ActionRegistry,
+        templates: &Templates,
+        value: Value,
+    ) -> Result<Self, ActionError> {
+        if value.get("template").is_some() {
+            let versioned: VersionedScriptTemplate =
+                serde_json::from_value(value).map_err(ActionError::ParseError)?;
+            return Ok(Self::Template(versioned.into_latest()));
+        }
+
+        if value.get("scripts").is_some() {
+            let versioned: VersionedScriptBundle =
+                serde_json::from_value(value).map_err(ActionError::ParseError)?;
+            return SmashlineScript::from_bundle(registry, templates, versioned.into_latest())
+                .map(Self::Bundle);
+        }
+
+        if value.get("patch").is_none() {
+            return SmashlineScript::parse_value(registry, templates, value).map(Self::Script);
+        }
+
+        let versioned: VersionedScriptPatch =
+            serde_json::from_value(value).map_err(ActionError::ParseError)?;
+        let patch = versioned.into_latest();
+        patch.validate(registry, templates)?;
+
+        Ok(Self::Patch(patch))
+    }
+
+    /// Text files always define a script, but they can still instantiate templates
+    pub fn from_text(
+        registry: &ActionRegistry,
+        templates: &Templates,
+        source: impl AsRef<str>,
+    ) -> Result<Self, ActionError> {
+        SmashlineScript::parse_text(registry, templates, source.as_ref()).map(Self::Script)
+    }
 }
@@ -0,0 +1,163 @@
+//! Descriptions of the registered actions, for editors and documentation
+//!
+//! Most of the metadata comes from an action's schema, so the doc comments of the action and its
+//! fields, and the defaults of its `#[serde(default)]` fields, are picked up without any extra
+//! work. [`Action::metadata`](crate::action::Action::metadata) fills in the rest, such as the
+//! category of scripts the action is meant for.
+
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{Schema, SchemaObject},
+};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::asset::Category;
+
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct FieldMetadata {
+    pub name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// The value an editor should start the field with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<Value>,
+}
+
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct ActionMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// The category of scripts the action belongs in, `None` for actions that work in any script
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<Category>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldMetadata>,
+}
+
+impl ActionMetadata {
+    pub fn new(description: impl Into<String>) -> Self {
+        Self {
+            description: Some(description.into()),
+            ..Default::default()
+        }
+    }
+
+    pub fn category(mut self, category: Category) -> Self {
+        self.category = Some(category);
+        self
+    }
+
+    /// Documents a field, for fields whose doc comments aren't enough
+    pub fn field(mut self, name: &str, description: impl Into<String>) -> Self {
+        self.field_mut(name).description = Some(description.into());
+        self
+    }
+
+    /// Uses every field of `value` as the default of that field, usually with `Default::default()`
+    pub fn defaults(mut self, value: &impl Serialize) -> Self {
+        if let Ok(Value::Object(map)) = serde_json::to_value(value) {
+            for (name, default) in map {
+                self.field_mut(&name).default = Some(default);
+            }
+        }
+        self
+    }
+
+    pub fn get_field(&self, name: &str) -> Option<&FieldMetadata> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+
+    fn field_mut(&mut self, name: &str) -> &mut FieldMetadata {
+        let index = match self.fields.iter().position(|field| field.name == name) {
+            Some(index) => index,
+            None => {
+                self.fields.push(FieldMetadata {
+                    name: name.to_string(),
+                    ..Default::default()
+                });
+                self.fields.len() - 1
+            }
+        };
+
+        &mut self.fields[index]
+    }
+
+    /// Fills in everything that wasn't set explicitly from the action's schema
+    pub(crate) fn fill_from_schema(&mut self, generator: &SchemaGenerator, schema: &Schema) {
+        let Some(object) = dereference(generator, schema) else {
+            return;
+        };
+
+        if self.description.is_none() {
+            self.description = description(object);
+        }
+
+        let Some(validation) = object.object.as_ref() else {
+            return;
+        };
+
+        for (name, property) in validation.properties.iter() {
+            let Some(property) = dereference_property(generator, property) else {
+                continue;
+            };
+
+            let field = self.field_mut(name);
+            if field.description.is_none() {
+                field.description = description(property);
+            }
+            if field.default.is_none() {
+                field.default = property
+                    .metadata
+                    .as_ref()
+                    .and_then(|metadata| metadata.default.clone());
+            }
+        }
+    }
+}
+
+fn description(object: &SchemaObject) -> Option<String> {
+    object
+        .metadata
+        .as_ref()
+        .and_then(|metadata| metadata.description.clone())
+}
+
+fn dereference<'a>(generator: &'a SchemaGenerator, schema: &'a Schema) -> Option<&'a SchemaObject> {
+    match generator.dereference(schema).unwrap_or(schema) {
+        Schema::Object(object) => Some(object),
+        Schema::Bool(_) => None,
+    }
+}
+
+/// Properties keep their own doc comment and default, even when they refer to another definition
+fn dereference_property<'a>(
+    generator: &'a SchemaGenerator,
+    schema: &'a Schema,
+) -> Option<&'a SchemaObject> {
+    match schema {
+        Schema::Object(object) if object.metadata.is_some() => Some(object),
+        schema => dereference(generator, schema),
+    }
+}
+
+/// A registered action, as listed by [`ActionRegistry::actions`](crate::action::ActionRegistry::actions)
+#[derive(Serialize, Debug, Clone)]
+pub struct ActionInfo {
+    pub name: String,
+    pub acmd_name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+
+    pub is_control: bool,
+
+    pub metadata: ActionMetadata,
+}
@@ -0,0 +1,35 @@
+//! Watching scripts and actions as they run, for logging, profiling or recording what a script did
+//!
+//! Observers are added to an [`ActionRegistry`](crate::action::ActionRegistry) with
+//! [`add_observer`](crate::action::ActionRegistry::add_observer) and see every action that the
+//! registry runs, including the actions nested in control actions. A control action ends after all
+//! of the actions it ran.
+//!
+//! Every event comes with the context the action runs against, so observers can find out which
+//! agent ran it with [`ExecutionContext::agent`] and read its state through the context.
+
+use crate::{
+    action::{ActionError, DynamicAction},
+    context::ExecutionContext,
+    SmashlineScript,
+};
+
+/// Every method does nothing by default, so observers only implement the events they need
+pub trait ExecutionObserver: Send + Sync {
+    /// Called before the first action of a script run with
+    /// [`ActionRegistry::execute_script`](crate::action::ActionRegistry::execute_script)
+    fn on_script_start(&self, _script: &SmashlineScript, _context: &mut dyn ExecutionContext) {}
+
+    /// Called once every action of the script has run, even when some of them failed
+    fn on_script_end(&self, _script: &SmashlineScript, _context: &mut dyn ExecutionContext) {}
+
+    fn on_action_start(&self, _action: &DynamicAction, _context: &mut dyn ExecutionContext) {}
+
+    fn on_action_end(
+        &self,
+        _action: &DynamicAction,
+        _context: &mut dyn ExecutionContext,
+        _result: &Result<(), ActionError>,
+    ) {
+    }
+}
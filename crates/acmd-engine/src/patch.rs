@@ -0,0 +1,378 @@
+//! Patches, which change the actions of another loaded script instead of replacing it
+//!
+//! ```json
+//! {
+//!     "version": "0.2.0",
+//!     "agent": "mario",
+//!     "category": "Game",
+//!     "patch": "game_attack11",
+//!     "operations": [
+//!         { "Edit": { "target": { "action": "Attack.set", "fields": { "id": 0 } }, "set": { "damage": 3.0 } } },
+//!         { "Insert": { "at": { "after": { "action": "WaitUntil", "fields": 5.0 } }, "actions": [...] } },
+//!         { "Remove": { "target": 4 } }
+//!     ]
+//! }
+//! ```
+//!
+//! Operations run in order on the top level actions of the patched script, so indices refer to the
+//! actions as they are after the previous operations. A matcher selects every action with the given
+//! name whose value contains `fields`, or only the `nth` of them.
+
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use crate::{
+    action::{ActionError, ActionRegistry},
+    asset::{LatestScriptPatch, LatestSmashlineScript, VersionedScriptPatch},
+    binary,
+    diagnostic::{Diagnostics, PathSegment},
+    template::Templates,
+    SmashlineScript,
+};
+
+#[derive(Error, Debug)]
+pub enum PatchError {
+    #[error("Index {index} is out of range for {len} action(s)")]
+    IndexOutOfRange { index: usize, len: usize },
+
+    #[error("No action matches {0}")]
+    NoMatch(Selector),
+}
+
+/// Selects actions by their matching name and fields
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ActionMatch {
+    /// The name of the action, either its registered name or its ACMD name
+    pub action: String,
+
+    /// Fields the action's value must contain, or the whole value when it isn't a map
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fields: Option<Value>,
+
+    /// Only selects the nth matching action, starting at 0, instead of all of them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nth: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum Selector {
+    Index(usize),
+    Match(ActionMatch),
+}
+
+impl Display for Selector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Index(index) => write!(f, "action {index}"),
+            Self::Match(matcher) => {
+                write!(f, "'{}'", matcher.action)?;
+                if let Some(fields) = matcher.fields.as_ref() {
+                    write!(f, " with {fields}")?;
+                }
+                if let Some(nth) = matcher.nth {
+                    write!(f, " (match {nth})")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum Position {
+    Start,
+    End,
+    Before(Selector),
+    After(Selector),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum PatchOperation {
+    Insert {
+        at: Position,
+        actions: Vec<Value>,
+    },
+    Remove {
+        target: Selector,
+    },
+    Replace {
+        target: Selector,
+        actions: Vec<Value>,
+    },
+
+    /// Merges `set` into the value of the selected actions, where `null` removes a field
+    Edit {
+        target: Selector,
+        set: Value,
+    },
+}
+
+impl PatchOperation {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Insert { .. } => "Insert",
+            Self::Remove { .. } => "Remove",
+            Self::Replace { .. } => "Replace",
+            Self::Edit { .. } => "Edit",
+        }
+    }
+
+    fn actions(&self) -> &[Value] {
+        match self {
+            Self::Insert { actions, .. } | Self::Replace { actions, .. } => actions,
+            Self::Remove { .. } | Self::Edit { .. } => &[],
+        }
+    }
+
+    fn apply(&self, registry: &ActionRegistry, values: &mut Vec<Value>) -> Result<(), PatchError> {
+        match self {
+            Self::Insert { at, actions } => {
+                let indices = match at {
+                    Position::Start => vec![0],
+                    Position::End => vec![values.len()],
+                    Position::Before(selector) => select(registry, selector, values)?,
+                    Position::After(selector) => select(registry, selector, values)?
+                        .into_iter()
+                        .map(|index| index + 1)
+                        .collect(),
+                };
+
+                // Going backwards keeps the earlier indices valid
+                for index in indices.into_iter().rev() {
+                    values.splice(index..index, actions.iter().cloned());
+                }
+            }
+            Self::Remove { target } => {
+                for index in select(registry, target, values)?.into_iter().rev() {
+                    values.remove(index);
+                }
+            }
+            Self::Replace { target, actions } => {
+                for index in select(registry, target, values)?.into_iter().rev() {
+                    values.splice(index..=index, actions.iter().cloned());
+                }
+            }
+            Self::Edit { target, set } => {
+                for index in select(registry, target, values)? {
+                    edit(&mut values[index], set);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Splits an action into its name and value, the same way [`ActionRegistry::as_action`] does
+fn split(action: &Value) -> Option<(&str, &Value)> {
+    match action {
+        Value::String(name) => Some((name, &Value::Null)),
+        Value::Object(map) if map.len() == 1 => map.iter().next().map(|(k, v)| (k.as_str(), v)),
+        _ => None,
+    }
+}
+
+/// Whether `value` has everything in `pattern`, where maps only need to contain the pattern's keys
+fn contains(value: &Value, pattern: &Value) -> bool {
+    match (value, pattern) {
+        (Value::Object(value), Value::Object(pattern)) => pattern
+            .iter()
+            .all(|(key, pattern)| value.get(key).is_some_and(|value| contains(value, pattern))),
+        // 5 and 5.0 are the same to the actions, which are deserialized into their own types
+        (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+        (value, pattern) => value == pattern,
+    }
+}
+
+fn select(
+    registry: &ActionRegistry,
+    selector: &Selector,
+    values: &[Value],
+) -> Result<Vec<usize>, PatchError> {
+    let matcher = match selector {
+        Selector::Index(index) if *index < values.len() => return Ok(vec![*index]),
+        Selector::Index(index) => {
+            return Err(PatchError::IndexOutOfRange {
+                index: *index,
+                len: values.len(),
+            })
+        }
+        Selector::Match(matcher) => matcher,
+    };
+
+    let name = registry
+        .resolve_name(&matcher.action)
+        .unwrap_or_else(|| matcher.action.clone());
+
+    let matches = values.iter().enumerate().filter(|(_, action)| {
+        split(action).is_some_and(|(action_name, value)| {
+            action_name == name
+                && matcher
+                    .fields
+                    .as_ref()
+                    .map_or(true, |fields| contains(value, fields))
+        })
+    });
+
+    let indices: Vec<usize> = match matcher.nth {
+        Some(nth) => matches
+            .map(|(index, _)| index)
+            .nth(nth)
+            .into_iter()
+            .collect(),
+        None => matches.map(|(index, _)| index).collect(),
+    };
+
+    if indices.is_empty() {
+        return Err(PatchError::NoMatch(selector.clone()));
+    }
+
+    Ok(indices)
+}
+
+/// Applies a JSON merge patch to a value
+fn merge(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+
+    let target = target.as_object_mut().unwrap();
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            merge(target.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+fn edit(action: &mut Value, set: &Value) {
+    let Some((name, value)) = split(action) else {
+        return;
+    };
+
+    let name = name.to_string();
+    let mut value = value.clone();
+    merge(&mut value, set);
+
+    *action = if value.is_null() {
+        Value::String(name)
+    } else {
+        let mut map = Map::new();
+        map.insert(name, value);
+        Value::Object(map)
+    };
+}
+
+impl LatestScriptPatch {
+    /// Reads a patch file, returning `None` when the file is a script or a template instead. The
+    /// patch still has to be checked with [`LatestScriptPatch::validate`].
+    pub fn read_json(json: impl AsRef<str>) -> Result<Option<Self>, ActionError> {
+        let value = serde_json::from_str(json.as_ref()).map_err(ActionError::ParseError)?;
+        Self::read_value(value)
+    }
+
+    /// Same as [`LatestScriptPatch::read_json`], for the binary format
+    pub fn read_binary(data: impl AsRef<[u8]>) -> Result<Option<Self>, ActionError> {
+        let value = binary::decode(data.as_ref()).map_err(ActionError::DecodeError)?;
+        Self::read_value(value)
+    }
+
+    /// Same as [`LatestScriptPatch::read_json`], for a file that was already parsed
+    pub fn read_value(value: Value) -> Result<Option<Self>, ActionError> {
+        // Told apart the same way as in `ScriptFile`, where templates and bundles come first
+        if value.get("template").is_some()
+            || value.get("scripts").is_some()
+            || value.get("patch").is_none()
+        {
+            return Ok(None);
+        }
+
+        let versioned: VersionedScriptPatch =
+            serde_json::from_value(value).map_err(ActionError::ParseError)?;
+        Ok(Some(versioned.into_latest()))
+    }
+
+    /// Whether this patch applies to `script`
+    pub fn targets(&self, script: &SmashlineScript) -> bool {
+        self.agent == script.agent
+            && self.category == script.category
+            && self.patch == script.replace
+    }
+
+    /// Checks that every action the patch adds can be loaded, reporting each one that can't
+    pub fn validate(
+        &self,
+        registry: &ActionRegistry,
+        templates: &Templates,
+    ) -> Result<(), ActionError> {
+        let mut diagnostics = Diagnostics(vec![]);
+
+        for (index, operation) in self.operations.iter().enumerate() {
+            for (action_index, action) in operation.actions().iter().enumerate() {
+                if let Err(error) = templates.load(registry, action.clone()) {
+                    let root = vec![
+                        PathSegment::Key("operations".to_string()),
+                        PathSegment::Index(index),
+                        PathSegment::Key(operation.name().to_string()),
+                        PathSegment::Key("actions".to_string()),
+                        PathSegment::Index(action_index),
+                    ];
+                    diagnostics.push_at(index, root, error);
+                }
+            }
+        }
+
+        if !diagnostics.0.is_empty() {
+            return Err(ActionError::InvalidActions(diagnostics));
+        }
+
+        Ok(())
+    }
+
+    /// Creates a new script with the operations applied to the actions of `script`
+    pub fn apply(
+        &self,
+        registry: &ActionRegistry,
+        templates: &Templates,
+        script: &SmashlineScript,
+    ) -> Result<SmashlineScript, ActionError> {
+        let mut values = script
+            .actions
+            .iter()
+            .map(|action| registry.as_value(action))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (index, operation) in self.operations.iter().enumerate() {
+            operation
+                .apply(registry, &mut values)
+                .map_err(|error| ActionError::PatchError {
+                    operation: index,
+                    error,
+                })?;
+        }
+
+        SmashlineScript::from_asset(
+            registry,
+            templates,
+            LatestSmashlineScript {
+                agent: script.agent,
+                category: script.category,
+                replace: script.replace,
+                costumes: script.costumes.clone(),
+                metadata: script.metadata.clone(),
+                actions: values,
+            },
+        )
+    }
+}
@@ -0,0 +1,90 @@
+//! JSON Schema generation for scripts and the actions registered to an [`ActionRegistry`](crate::action::ActionRegistry)
+//!
+//! Every action in a script is referenced through the `Action` definition, which is the union of
+//! every registered action's schema. Control actions use [`action_list`] for their nested blocks so
+//! the nested actions are validated as well.
+
+use schemars::{
+    gen::{SchemaGenerator, SchemaSettings},
+    schema::{
+        ArrayValidation, InstanceType, ObjectValidation, Schema, SchemaObject, SingleOrVec,
+        SubschemaValidation,
+    },
+};
+use serde_json::Value;
+
+/// The name of the definition which every registered action is a part of
+pub const ACTION_DEFINITION: &str = "Action";
+
+pub fn generator() -> SchemaGenerator {
+    SchemaSettings::draft07().into_generator()
+}
+
+/// A reference to the definition containing every registered action
+pub fn action_ref(generator: &SchemaGenerator) -> Schema {
+    Schema::new_ref(format!(
+        "{}{}",
+        generator.settings().definitions_path,
+        ACTION_DEFINITION
+    ))
+}
+
+/// The schema for a list of actions, such as the body of a loop
+pub fn action_list(generator: &mut SchemaGenerator) -> Schema {
+    SchemaObject {
+        instance_type: Some(InstanceType::Array.into()),
+        array: Some(Box::new(ArrayValidation {
+            items: Some(SingleOrVec::Single(Box::new(action_ref(generator)))),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+    .into()
+}
+
+fn accepts_null(generator: &SchemaGenerator, schema: &Schema) -> bool {
+    let object = match generator.dereference(schema).unwrap_or(schema) {
+        Schema::Object(object) => object,
+        Schema::Bool(_) => return false,
+    };
+
+    match &object.instance_type {
+        Some(SingleOrVec::Single(ty)) => **ty == InstanceType::Null,
+        Some(SingleOrVec::Vec(tys)) => tys.contains(&InstanceType::Null),
+        None => false,
+    }
+}
+
+/// Wraps the schema of an action's value into the single key map (or bare name) it is written as
+pub fn wrap_action(generator: &SchemaGenerator, name: &str, value: Schema) -> Schema {
+    let mut object = ObjectValidation::default();
+    object.required.insert(name.to_string());
+    object.properties.insert(name.to_string(), value.clone());
+    object.additional_properties = Some(Box::new(Schema::Bool(false)));
+
+    let map: Schema = SchemaObject {
+        instance_type: Some(InstanceType::Object.into()),
+        object: Some(Box::new(object)),
+        ..Default::default()
+    }
+    .into();
+
+    if !accepts_null(generator, &value) {
+        return map;
+    }
+
+    let bare_name: Schema = SchemaObject {
+        const_value: Some(Value::String(name.to_string())),
+        ..Default::default()
+    }
+    .into();
+
+    SchemaObject {
+        subschemas: Some(Box::new(SubschemaValidation {
+            any_of: Some(vec![bare_name, map]),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+    .into()
+}
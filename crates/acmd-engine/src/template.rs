@@ -0,0 +1,423 @@
+//! Templates, which are lists of actions with named parameters that scripts instantiate
+//!
+//! ```json
+//! {
+//!     "version": "0.2.0",
+//!     "template": "jab_hitbox",
+//!     "parameters": {
+//!         "damage": {},
+//!         "angle": { "default": 361 },
+//!         "bone": { "default": "top", "description": "The bone the hitbox follows" }
+//!     },
+//!     "actions": [
+//!         { "Attack.set": { "id": 0, "bone": "$bone", "damage": "$damage", "angle": { "Normal": "$angle" }, ... } }
+//!     ]
+//! }
+//! ```
+//!
+//! Any string that is exactly `$name` is replaced by the value of the parameter, and `$$` anywhere
+//! in a string escapes a literal `$`. Inside longer strings, `$name` is replaced by the value
+//! of a number parameter, so [expressions](crate::expr) can be built from the parameters, like
+//! `"$damage * 1.2"`. Scripts use a template with the `Template` action, which is replaced by the
+//! template's actions before the actions are loaded:
+//!
+//! ```json
+//! { "Template": { "name": "jab_hitbox", "with": { "damage": 2.2 } } }
+//! ```
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use crate::{
+    action::{ActionError, ActionRegistry, DynamicAction},
+    asset::{LatestScriptTemplate, VersionedScriptTemplate},
+    binary,
+};
+
+/// The name of the action that instantiates a template
+pub const TEMPLATE_ACTION: &str = "Template";
+
+#[derive(Error, Debug)]
+pub enum TemplateError {
+    #[error("Template '{0}' is not loaded")]
+    NotFound(String),
+
+    #[error("Template '{template}' has no parameter '{parameter}'")]
+    UnknownParameter { template: String, parameter: String },
+
+    #[error("Template '{template}' needs a value for parameter '{parameter}'")]
+    MissingParameter { template: String, parameter: String },
+
+    #[error("Template '{0}' instantiates itself")]
+    Recursive(String),
+
+    #[error("Invalid template instance: {0}")]
+    InvalidInstance(serde_json::Error),
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct TemplateParameter {
+    /// The value used when an instance doesn't set the parameter, which is required without one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<Value>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// The value of a `Template` action
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TemplateInstance {
+    pub name: String,
+
+    #[serde(default, rename = "with", skip_serializing_if = "Map::is_empty")]
+    pub arguments: Map<String, Value>,
+}
+
+impl TemplateInstance {
+    /// Reads the instance from an action, or `None` if it isn't a `Template` action
+    pub fn from_action(action: &Value) -> Option<Result<Self, TemplateError>> {
+        let map = action.as_object().filter(|map| map.len() == 1)?;
+        let value = map.get(TEMPLATE_ACTION)?;
+        Some(serde_json::from_value(value.clone()).map_err(TemplateError::InvalidInstance))
+    }
+}
+
+/// The templates that scripts can instantiate, by name
+#[derive(Default)]
+pub struct Templates(BTreeMap<String, LatestScriptTemplate>);
+
+impl Templates {
+    /// Adds a template, returning the one it replaces with the same name
+    pub fn insert(&mut self, template: LatestScriptTemplate) -> Option<LatestScriptTemplate> {
+        self.0.insert(template.template.clone(), template)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&LatestScriptTemplate> {
+        self.0.get(name)
+    }
+
+    /// Reads a template file, returning `None` when the file is a script or a patch instead
+    pub fn read_json(json: impl AsRef<str>) -> Result<Option<LatestScriptTemplate>, ActionError> {
+        let value = serde_json::from_str(json.as_ref()).map_err(ActionError::ParseError)?;
+        Self::read_value(value)
+    }
+
+    /// Same as [`Templates::read_json`], for the binary format
+    pub fn read_binary(
+        data: impl AsRef<[u8]>,
+    ) -> Result<Option<LatestScriptTemplate>, ActionError> {
+        let value = binary::decode(data.as_ref()).map_err(ActionError::DecodeError)?;
+        Self::read_value(value)
+    }
+
+    /// Same as [`Templates::read_json`], for a file that was already parsed
+    pub fn read_value(value: Value) -> Result<Option<LatestScriptTemplate>, ActionError> {
+        if value.get("template").is_none() {
+            return Ok(None);
+        }
+
+        let versioned: VersionedScriptTemplate =
+            serde_json::from_value(value).map_err(ActionError::ParseError)?;
+        Ok(Some(versioned.into_latest()))
+    }
+
+    /// Expands a top level action into the actions it stands for, which is the action itself with
+    /// its nested templates expanded unless it is a `Template` action
+    pub fn expand(&self, action: Value) -> Result<Vec<Value>, TemplateError> {
+        self.expand_action(action, &mut vec![])
+    }
+
+    /// Expands a top level action and converts the result into actions.
+    ///
+    /// Errors in the actions of a template are wrapped in [`ActionError::InTemplate`], since their
+    /// paths are inside of the template rather than the script.
+    pub fn load(
+        &self,
+        registry: &ActionRegistry,
+        action: Value,
+    ) -> Result<Vec<DynamicAction>, ActionError> {
+        let template = match TemplateInstance::from_action(&action) {
+            Some(Ok(instance)) => Some(instance.name),
+            Some(Err(error)) => return Err(ActionError::TemplateError(error)),
+            None => None,
+        };
+
+        let values = self.expand(action).map_err(ActionError::TemplateError)?;
+        let mut actions = Vec::with_capacity(values.len());
+        let mut errors = vec![];
+
+        for (index, value) in values.into_iter().enumerate() {
+            match (registry.as_action(value), template.as_ref()) {
+                (Ok(action), _) => actions.push(action),
+                (Err(error), Some(template)) => errors.push(ActionError::InTemplate {
+                    template: template.clone(),
+                    error: Box::new(error.at_index(index)),
+                }),
+                (Err(error), None) => errors.push(error),
+            }
+        }
+
+        match ActionError::from_errors(errors) {
+            Some(error) => Err(error),
+            None => Ok(actions),
+        }
+    }
+
+    fn expand_action(
+        &self,
+        action: Value,
+        stack: &mut Vec<String>,
+    ) -> Result<Vec<Value>, TemplateError> {
+        match TemplateInstance::from_action(&action) {
+            Some(instance) => self.instantiate(instance?, stack),
+            None => self.expand_value(action, stack).map(|action| vec![action]),
+        }
+    }
+
+    /// Expands the templates in every list nested in `value`, since they can be blocks of actions
+    fn expand_value(&self, value: Value, stack: &mut Vec<String>) -> Result<Value, TemplateError> {
+        match value {
+            Value::Array(values) => {
+                let mut expanded = Vec::with_capacity(values.len());
+                for value in values {
+                    expanded.extend(self.expand_action(value, stack)?);
+                }
+                Ok(Value::Array(expanded))
+            }
+            Value::Object(map) => map
+                .into_iter()
+                .map(|(key, value)| Ok((key, self.expand_value(value, stack)?)))
+                .collect::<Result<Map<_, _>, _>>()
+                .map(Value::Object),
+            value => Ok(value),
+        }
+    }
+
+    fn instantiate(
+        &self,
+        instance: TemplateInstance,
+        stack: &mut Vec<String>,
+    ) -> Result<Vec<Value>, TemplateError> {
+        let template = self
+            .get(&instance.name)
+            .ok_or_else(|| TemplateError::NotFound(instance.name.clone()))?;
+
+        if stack.contains(&instance.name) {
+            return Err(TemplateError::Recursive(instance.name));
+        }
+
+        if let Some(parameter) = instance
+            .arguments
+            .keys()
+            .find(|parameter| !template.parameters.contains_key(*parameter))
+        {
+            return Err(TemplateError::UnknownParameter {
+                template: instance.name.clone(),
+                parameter: parameter.clone(),
+            });
+        }
+
+        let mut arguments = Map::new();
+        for (parameter, declaration) in template.parameters.iter() {
+            let value = instance
+                .arguments
+                .get(parameter)
+                .or(declaration.default.as_ref())
+                .ok_or_else(|| TemplateError::MissingParameter {
+                    template: instance.name.clone(),
+                    parameter: parameter.clone(),
+                })?;
+            arguments.insert(parameter.clone(), value.clone());
+        }
+
+        stack.push(instance.name);
+        let mut actions = vec![];
+        for action in template.actions.iter() {
+            let action = substitute(action.clone(), &arguments);
+            actions.extend(self.expand_action(action, stack)?);
+        }
+        stack.pop();
+
+        Ok(actions)
+    }
+}
+
+/// Replaces every `$name` string with the value of the argument
+fn substitute(value: Value, arguments: &Map<String, Value>) -> Value {
+    match value {
+        Value::String(string) => {
+            let argument = string
+                .strip_prefix('$')
+                .and_then(|name| arguments.get(name));
+            match argument {
+                Some(argument) => argument.clone(),
+                None => Value::String(substitute_numbers(&string, arguments)),
+            }
+        }
+        Value::Array(values) => Value::Array(
+            values
+                .into_iter()
+                .map(|value| substitute(value, arguments))
+                .collect(),
+        ),
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| (key, substitute(value, arguments)))
+                .collect(),
+        ),
+        value => value,
+    }
+}
+
+/// Replaces every `$name` inside of a string whose argument is a number with the number, and every
+/// `$$` with a literal `$`
+fn substitute_numbers(string: &str, arguments: &Map<String, Value>) -> String {
+    let mut out = String::with_capacity(string.len());
+    let mut rest = string;
+
+    while let Some(start) = rest.find('$') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+
+        if let Some(escaped) = rest.strip_prefix('$') {
+            out.push('$');
+            rest = escaped;
+            continue;
+        }
+
+        let end = rest
+            .find(|char: char| !char.is_ascii_alphanumeric() && char != '_')
+            .unwrap_or(rest.len());
+        match arguments.get(&rest[..end]) {
+            Some(Value::Number(number)) => out.push_str(&number.to_string()),
+            _ => {
+                out.push('$');
+                out.push_str(&rest[..end]);
+            }
+        }
+        rest = &rest[end..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn templates(templates: &[Value]) -> Templates {
+        let mut loaded = Templates::default();
+        for template in templates {
+            let template = Templates::read_value(template.clone()).unwrap().unwrap();
+            loaded.insert(template);
+        }
+        loaded
+    }
+
+    fn template(name: &str, parameters: Value, actions: Value) -> Value {
+        json!({ "version": "0.2.0", "template": name, "parameters": parameters, "actions": actions })
+    }
+
+    fn instance(name: &str, arguments: Value) -> Value {
+        json!({ "Template": { "name": name, "with": arguments } })
+    }
+
+    #[test]
+    fn substitution() {
+        let templates = templates(&[template(
+            "hitbox",
+            json!({ "damage": {}, "angle": { "default": 361 }, "bone": { "default": "top" } }),
+            json!([
+                { "Attack.set": { "bone": "$bone", "angle": { "Normal": "$angle" }, "damage": "$damage * 1.2" } },
+                { "Note": "costs $$5, $bone is not a number" }
+            ]),
+        )]);
+
+        let actions = templates
+            .expand(instance(
+                "hitbox",
+                json!({ "damage": 3, "bone": { "Hash": 1 } }),
+            ))
+            .unwrap();
+        assert_eq!(
+            actions,
+            [
+                json!({ "Attack.set": { "bone": { "Hash": 1 }, "angle": { "Normal": 361 }, "damage": "3 * 1.2" } }),
+                json!({ "Note": "costs $5, $bone is not a number" }),
+            ]
+        );
+    }
+
+    #[test]
+    fn nested() {
+        let templates = templates(&[
+            template(
+                "flag",
+                json!({ "flag": {} }),
+                json!([{ "Work.on_flag": "$flag" }]),
+            ),
+            template(
+                "flags",
+                json!({ "flag": {} }),
+                json!([
+                    { "Template": { "name": "flag", "with": { "flag": "$flag" } } },
+                    { "If": { "condition": "IsExcute", "then": [{ "Template": { "name": "flag", "with": { "flag": "OTHER" } } }] } }
+                ]),
+            ),
+        ]);
+
+        assert_eq!(
+            templates
+                .expand(instance("flags", json!({ "flag": "FLAG" })))
+                .unwrap(),
+            [
+                json!({ "Work.on_flag": "FLAG" }),
+                json!({ "If": { "condition": "IsExcute", "then": [{ "Work.on_flag": "OTHER" }] } }),
+            ]
+        );
+
+        // Templates are expanded inside of the blocks of any action, not only of other templates
+        let action = json!({ "If": { "then": [instance("flag", json!({ "flag": "FLAG" }))] } });
+        assert_eq!(
+            templates.expand(action).unwrap(),
+            [json!({ "If": { "then": [{ "Work.on_flag": "FLAG" }] } })]
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let templates = templates(&[
+            template("a", json!({}), json!([{ "Template": { "name": "b" } }])),
+            template("b", json!({}), json!([{ "Template": { "name": "a" } }])),
+            template(
+                "flag",
+                json!({ "flag": {} }),
+                json!([{ "Work.on_flag": "$flag" }]),
+            ),
+        ]);
+
+        assert!(matches!(
+            templates.expand(instance("a", json!({}))),
+            Err(TemplateError::Recursive(name)) if name == "a"
+        ));
+        assert!(matches!(
+            templates.expand(instance("missing", json!({}))),
+            Err(TemplateError::NotFound(_))
+        ));
+        assert!(matches!(
+            templates.expand(instance("flag", json!({}))),
+            Err(TemplateError::MissingParameter { .. })
+        ));
+        assert!(matches!(
+            templates.expand(instance("flag", json!({ "flag": "FLAG", "other": 1 }))),
+            Err(TemplateError::UnknownParameter { .. })
+        ));
+    }
+}
@@ -0,0 +1,254 @@
+//! An absolute frame view of a list of actions
+//!
+//! Scripts only say how long to wait between actions, which makes questions like "what happens on
+//! frame 12" hard to answer. A [`Timeline`] resolves every wait into the frame each action runs on,
+//! and can be turned back into a list of actions with canonical waits.
+//!
+//! Frames are the script's own frame numbers, the ones that `WaitUntil` takes, starting at `0.0`.
+//! They follow the animation, so once an action changes the motion rate they stop matching the
+//! frames of the game. [`Timeline::game_frame`] converts between the two.
+
+use std::borrow::Borrow;
+
+use thiserror::Error;
+
+use crate::action::{ActionRegistry, DynamicAction};
+
+/// How an action makes the script wait, see [`Action::wait`](crate::action::Action::wait)
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Wait {
+    /// Waits until the given frame, doing nothing if it has already passed
+    Until(f32),
+
+    /// Waits for the given number of frames
+    For(f32),
+}
+
+/// How an action changes the motion rate, see
+/// [`Action::motion_rate`](crate::action::Action::motion_rate)
+///
+/// A rate of `0.5` plays the animation at half speed, so every frame of the script takes two frames
+/// of the game.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum MotionRate {
+    /// Plays at the given rate from the frame of the action on
+    Set(f32),
+
+    /// Plays at the given rate from `start` until `end`, then goes back to the rate that was set with
+    /// [`MotionRate::Set`]
+    Range { start: f32, end: f32, rate: f32 },
+}
+
+/// Which kind of wait is written between events when converting a timeline back into actions
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum WaitStyle {
+    /// Every wait is a [`Wait::Until`] of the frame the next event is on
+    #[default]
+    Absolute,
+
+    /// Every wait is a [`Wait::For`] of the frames since the previous event
+    Relative,
+}
+
+#[derive(Error, Debug)]
+pub enum TimelineError {
+    #[error("Action {index} waits inside of a nested block, so the frames after it are unknown")]
+    NestedWait { index: usize },
+
+    #[error("Action {index} can wait in another script, so the frames after it are unknown")]
+    OpaqueWait { index: usize },
+
+    #[error("No registered action can represent {0:?}")]
+    NoWaitAction(Wait),
+}
+
+pub struct TimelineEvent<A = DynamicAction> {
+    pub frame: f32,
+    pub action: A,
+}
+
+/// The actions of a script with the frame they run on, sorted by frame.
+///
+/// Works with owned actions to edit a script, or with references to inspect one in place.
+pub struct Timeline<A = DynamicAction> {
+    pub events: Vec<TimelineEvent<A>>,
+
+    /// The frame the actions end on, which is later than the last event when they end with a wait
+    pub length: f32,
+}
+
+impl<A> Default for Timeline<A> {
+    fn default() -> Self {
+        Self {
+            events: vec![],
+            length: 0.0,
+        }
+    }
+}
+
+impl<A: Borrow<DynamicAction>> Timeline<A> {
+    /// Resolves the waits in `actions` into frames.
+    ///
+    /// Control actions are kept as a single event, which fails if any of their nested actions wait
+    /// since the frames after them would depend on the branch taken at runtime. It also fails on
+    /// actions which can wait somewhere else, like a `Call` of another script.
+    pub fn from_actions(
+        registry: &ActionRegistry,
+        actions: impl IntoIterator<Item = A>,
+    ) -> Result<Self, TimelineError> {
+        let mut timeline = Self::default();
+
+        for (index, action) in actions.into_iter().enumerate() {
+            match registry.wait(action.borrow()) {
+                Some(Wait::Until(frame)) => timeline.length = timeline.length.max(frame),
+                Some(Wait::For(frames)) => timeline.length += frames,
+                None => {
+                    let (mut nested_wait, mut opaque_wait) = (false, false);
+                    registry.visit(std::slice::from_ref(action.borrow()), &mut |nested| {
+                        nested_wait |= registry.wait(nested).is_some();
+                        opaque_wait |= registry.waits_elsewhere(nested);
+                    });
+
+                    if opaque_wait {
+                        return Err(TimelineError::OpaqueWait { index });
+                    }
+
+                    if nested_wait {
+                        return Err(TimelineError::NestedWait { index });
+                    }
+
+                    timeline.events.push(TimelineEvent {
+                        frame: timeline.length,
+                        action,
+                    });
+                }
+            }
+        }
+
+        Ok(timeline)
+    }
+
+    /// Every action that runs on `frame`
+    pub fn at(&self, frame: f32) -> impl Iterator<Item = &A> {
+        self.events
+            .iter()
+            .filter(move |event| event.frame == frame)
+            .map(|event| &event.action)
+    }
+
+    /// Every action that runs in `start..end`, along with its frame
+    pub fn between(&self, start: f32, end: f32) -> impl Iterator<Item = &TimelineEvent<A>> {
+        self.events
+            .iter()
+            .filter(move |event| event.frame >= start && event.frame < end)
+    }
+
+    /// The frame of the game that `frame` of the script is reached on, counting from when the
+    /// script starts.
+    ///
+    /// Motion rate changes inside of control actions are counted as if they always run, since they
+    /// are usually behind an `is_excute` check.
+    pub fn game_frame(&self, registry: &ActionRegistry, frame: f32) -> f32 {
+        let mut changes = vec![];
+        for event in self.events.iter().filter(|event| event.frame < frame) {
+            registry.visit(std::slice::from_ref(event.action.borrow()), &mut |action| {
+                if let Some(rate) = registry.motion_rate(action) {
+                    changes.push((event.frame, rate));
+                }
+            });
+        }
+
+        if changes.is_empty() {
+            return frame;
+        }
+
+        // The rate only changes on these frames, so it is constant between each of them
+        let mut bounds = vec![0.0, frame];
+        for (at, rate) in changes.iter() {
+            bounds.push(*at);
+            if let MotionRate::Range { start, end, .. } = rate {
+                bounds.push(start.max(*at));
+                bounds.push(*end);
+            }
+        }
+        bounds.retain(|bound| (0.0..=frame).contains(bound));
+        bounds.sort_by(f32::total_cmp);
+        bounds.dedup();
+
+        bounds
+            .windows(2)
+            .map(|window| (window[1] - window[0]) / rate_at(&changes, window[0]))
+            .sum()
+    }
+
+    /// How many frames of the game the actions take, see [`Timeline::game_frame`]
+    pub fn game_length(&self, registry: &ActionRegistry) -> f32 {
+        self.game_frame(registry, self.length)
+    }
+
+    /// Adds an action on `frame`, after every action already on that frame
+    pub fn insert(&mut self, frame: f32, action: A) {
+        let index = self.events.partition_point(|event| event.frame <= frame);
+        self.events.insert(index, TimelineEvent { frame, action });
+        self.length = self.length.max(frame);
+    }
+}
+
+/// The motion rate on `frame`, where the latest range that covers it wins over the latest rate set
+fn rate_at(changes: &[(f32, MotionRate)], frame: f32) -> f32 {
+    let range = changes.iter().rev().find_map(|(at, rate)| match rate {
+        MotionRate::Range { start, end, rate } if frame >= start.max(*at) && frame < *end => {
+            Some(*rate)
+        }
+        _ => None,
+    });
+
+    let set = || {
+        changes.iter().rev().find_map(|(at, rate)| match rate {
+            MotionRate::Set(rate) if frame >= *at => Some(*rate),
+            _ => None,
+        })
+    };
+
+    range.or_else(set).unwrap_or(1.0)
+}
+
+impl Timeline {
+    /// Turns the timeline back into actions, with a single wait before each frame that has events
+    pub fn into_actions(
+        self,
+        registry: &ActionRegistry,
+        style: WaitStyle,
+    ) -> Result<Vec<DynamicAction>, TimelineError> {
+        let mut actions = Vec::with_capacity(self.events.len());
+        let mut current = 0.0;
+
+        let wait_until = |actions: &mut Vec<DynamicAction>, current: &mut f32, frame: f32| {
+            if frame <= *current {
+                return Ok(());
+            }
+
+            let wait = match style {
+                WaitStyle::Absolute => Wait::Until(frame),
+                WaitStyle::Relative => Wait::For(frame - *current),
+            };
+
+            actions.push(
+                registry
+                    .wait_action(wait)
+                    .ok_or(TimelineError::NoWaitAction(wait))?,
+            );
+            *current = frame;
+            Ok(())
+        };
+
+        for event in self.events {
+            wait_until(&mut actions, &mut current, event.frame)?;
+            actions.push(event.action);
+        }
+
+        wait_until(&mut actions, &mut current, self.length)?;
+
+        Ok(actions)
+    }
+}
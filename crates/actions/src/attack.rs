@@ -1,5 +1,13 @@
-use acmd_engine::action::Action;
+use acmd_engine::{
+    action::Action,
+    asset::Category,
+    codegen,
+    context::{AttackAbsParams, AttackParams, CatchParams, ExecutionContext},
+    expr::Expr,
+    metadata::ActionMetadata,
+};
 use glam::{Vec2, Vec3};
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
 use serde::{Deserialize, Serialize};
 use std::num::NonZeroU32;
 
@@ -7,9 +15,8 @@ use std::num::NonZeroU32;
 use bevy_reflect::prelude::*;
 
 use hash40::{hash40, Hash40};
-use smash::{app::lua_bind::AttackModule, phx::Hash40 as GameHash40};
 
-use crate::{decl_action, SerdeHash40};
+use crate::{decl_action, lua_const::LuaConst, SerdeHash40};
 
 impl CollisionAttribute {
     pub const fn as_hash(&self) -> SerdeHash40 {
@@ -326,6 +333,25 @@ bitflags::bitflags! {
     }
 }
 
+// bitflags serializes the masks as a list of flag names, such as "GROUND | AIR"
+macro_rules! impl_flags_schema {
+    ($($mask:ident),*) => {
+        $(
+            impl JsonSchema for $mask {
+                fn schema_name() -> String {
+                    stringify!($mask).to_string()
+                }
+
+                fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+                    String::json_schema(generator)
+                }
+            }
+        )*
+    }
+}
+
+impl_flags_schema!(SituationMask, CategoryMask, PartMask);
+
 decl_action!(
     #[derive(Copy, Default)]
     pub enum AttackAngle {
@@ -339,6 +365,7 @@ decl_action!(
         StrongAutolink,
         Vector {
             relative_to: SerdeHash40,
+            #[schemars(with = "[f32; 2]")]
             offset: Vec2,
             num_frames: i32,
         },
@@ -368,6 +395,16 @@ decl_action!(
     }
 );
 
+impl Knockback {
+    /// The knockback growth, fixed knockback and base knockback passed to `ATTACK`
+    fn values(&self) -> (i32, i32, i32) {
+        match self {
+            Self::Fixed(value) => (0, *value, 0),
+            Self::Scaling { growth, base } => (*growth, 0, *base),
+        }
+    }
+}
+
 impl Default for Knockback {
     fn default() -> Self {
         Self::Scaling {
@@ -380,11 +417,34 @@ impl Default for Knockback {
 decl_action!(
     #[derive(Copy)]
     pub enum Shape {
-        Sphere(Vec3),
-        Capsule { p1: Vec3, p2: Vec3 },
+        Sphere(#[schemars(with = "[f32; 3]")] Vec3),
+        Capsule {
+            #[schemars(with = "[f32; 3]")]
+            p1: Vec3,
+            #[schemars(with = "[f32; 3]")]
+            p2: Vec3,
+        },
     }
 );
 
+impl Shape {
+    /// The offset of the box, and the second offset when it is a capsule
+    fn values(&self) -> ([f32; 3], Option<[f32; 3]>) {
+        match self {
+            Self::Sphere(p) => (p.to_array(), None),
+            Self::Capsule { p1, p2 } => (p1.to_array(), Some(p2.to_array())),
+        }
+    }
+
+    /// The three arguments of the second offset, which are `None` for a sphere
+    fn rust_offset2(&self) -> [String; 3] {
+        match self.values().1 {
+            Some(offset2) => offset2.map(|value| format!("Some({})", codegen::float(value))),
+            None => ["None", "None", "None"].map(String::from),
+        }
+    }
+}
+
 impl Default for Shape {
     fn default() -> Self {
         Self::Sphere(Vec3::default())
@@ -480,39 +540,57 @@ decl_action!(
 );
 
 decl_action!(
+    /// Places a hitbox, or replaces the one with the same id
     pub struct Attack {
+        /// The slot of the hitbox, setting a hitbox with an id in use replaces it
         pub id: u64,
+        /// Hitboxes with the same part can only hit a target once between them
         pub part: u64,
+        /// The bone the hitbox follows
         pub bone: SerdeHash40,
-        pub damage: f32,
+        /// The damage dealt, written as a number or an expression
+        pub damage: Expr,
         pub angle: AttackAngle,
         pub knockback: Knockback,
+        /// Extra frames of hitstun on top of the ones from knockback
         #[serde(default, skip_serializing_if = "Option::is_none")]
         pub extra_histun: Option<f32>,
-        pub radius: f32,
+        pub radius: Expr,
+        /// The offset of the hitbox from its bone, or both ends of a capsule
         pub shape: Shape,
+        /// Multiplies the hitlag of both the attacker and the target
         pub hitlag_mul: f32,
+        /// Multiplies how far the target can smash DI
         pub sdi_strength_mul: f32,
         pub shield_pushback: ShieldSetoff,
+        /// How the direction the target is sent in is decided
         pub hit_direction: HitDirection,
+        /// Knocks back every target as if it had the same weight
         pub set_weight: bool,
         pub shield_damage: ShieldDamage,
+        /// The chance for the target to trip, from 0.0 to 1.0
         #[serde(default, skip_serializing_if = "Option::is_none")]
         pub trip_chance: Option<f32>,
+        /// Lets the hitbox hit the same target again after this many frames
         #[serde(default, skip_serializing_if = "Option::is_none")]
         pub rehit_rate: Option<NonZeroU32>,
         pub reflectable: bool,
         pub absorbable: bool,
+        /// Hits without making the target flinch
         pub flinchless: bool,
         pub disable_hitlag: bool,
+        /// Whether the hit comes from the attacker's body rather than a projectile
         pub direct: bool,
         pub target_situations: SituationMask,
         pub target_categories: CategoryMask,
         pub target_parts: PartMask,
+        /// Lets the hitbox hit teammates
         pub friendly_fire: bool,
+        /// The effect of the hit, such as fire or sleep
         pub collision_effect: CollisionAttribute,
         pub sound_level: SoundLevel,
         pub sound: Sound,
+        /// The kind of attack, such as a punch or a kick
         pub region: AttackRegion,
     }
 );
@@ -523,14 +601,14 @@ impl Default for Attack {
             id: 0,
             part: 0,
             bone: SerdeHash40::new("top"),
-            damage: 15.0,
+            damage: Expr::from(15.0),
             angle: AttackAngle::Sakurai,
             knockback: Knockback::Scaling {
                 growth: 50,
                 base: 30,
             },
             extra_histun: None,
-            radius: 5.0,
+            radius: Expr::from(5.0),
             shape: Shape::Sphere(Vec3::default()),
             hitlag_mul: 1.0,
             sdi_strength_mul: 1.0,
@@ -557,76 +635,81 @@ impl Default for Attack {
     }
 }
 
+impl Attack {
+    fn shield_damage_value(&self) -> f32 {
+        match &self.shield_damage {
+            ShieldDamage::Transcendent => std::f32::NAN,
+            ShieldDamage::AdditionalDamage(damage) => *damage,
+        }
+    }
+}
+
 impl Action for Attack {
     const NAME: &'static str = "Attack.set";
+    const ACMD_NAME: &'static str = "ATTACK";
 
-    #[cfg(feature = "bevy_reflect")]
-    unsafe fn execute(&self, context: &mut smash::lua2cpp::L2CAgentBase) {
-        unimplemented!()
+    fn metadata() -> ActionMetadata {
+        ActionMetadata::default()
+            .category(Category::Game)
+            .defaults(&Self::default())
     }
 
-    #[cfg(not(feature = "bevy_reflect"))]
-    unsafe fn execute(&self, context: &mut smash::lua2cpp::L2CAgentBase) {
-        let (kbg, fkb, bkb) = match &self.knockback {
-            Knockback::Fixed(value) => (0, *value, 0),
-            Knockback::Scaling { growth, base } => (*growth, 0, *base),
-        };
+    fn to_rust(&self) -> Option<String> {
+        let (kbg, fkb, bkb) = self.knockback.values();
+        let ([x, y, z], _) = self.shape.values();
+        let [x2, y2, z2] = self.shape.rust_offset2();
 
-        let ([x, y, z], [x2, y2, z2]) = match &self.shape {
-            Shape::Sphere(p) => ([p.x, p.y, p.z], [None, None, None]),
-            Shape::Capsule { p1, p2 } => ([p1.x, p1.y, p1.z], [Some(p2.x), Some(p2.y), Some(p2.z)]),
-        };
-
-        smash_script::macros::ATTACK(
-            context,
-            self.id,
-            self.part,
-            GameHash40::new_raw(self.bone.0 .0),
-            self.damage,
-            self.angle.as_angle() as u64,
-            kbg,
-            fkb,
-            bkb,
-            self.radius,
-            x,
-            y,
-            z,
+        let args = [
+            self.id.to_string(),
+            self.part.to_string(),
+            codegen::hash(self.bone.0),
+            self.damage.to_rust(),
+            self.angle.as_angle().to_string(),
+            kbg.to_string(),
+            fkb.to_string(),
+            bkb.to_string(),
+            self.radius.to_rust(),
+            codegen::float(x),
+            codegen::float(y),
+            codegen::float(z),
             x2,
             y2,
             z2,
-            self.hitlag_mul,
-            self.sdi_strength_mul,
-            self.shield_pushback as i32,
-            self.hit_direction as i32,
-            self.set_weight,
-            match &self.shield_damage {
-                ShieldDamage::Transcendent => std::f32::NAN,
-                ShieldDamage::AdditionalDamage(damage) => *damage,
-            },
-            self.trip_chance.unwrap_or(-1.0),
-            self.rehit_rate.map(|rate| rate.get()).unwrap_or(0),
-            self.reflectable,
-            self.absorbable,
-            self.flinchless,
-            self.disable_hitlag,
-            self.direct,
-            self.target_situations.bits() as i32,
-            self.target_categories.bits() as i32,
-            self.target_parts.bits() as i32,
-            self.friendly_fire,
-            GameHash40::new_raw(self.collision_effect.as_hash().0 .0),
-            self.sound_level as i32,
-            self.sound as i32,
-            self.region as i32,
-        );
+            codegen::float(self.hitlag_mul),
+            codegen::float(self.sdi_strength_mul),
+            (self.shield_pushback as i32).to_string(),
+            (self.hit_direction as i32).to_string(),
+            self.set_weight.to_string(),
+            codegen::float(self.shield_damage_value()),
+            codegen::float(self.trip_chance.unwrap_or(-1.0)),
+            self.rehit_rate.map(|rate| rate.get()).unwrap_or(0).to_string(),
+            self.reflectable.to_string(),
+            self.absorbable.to_string(),
+            self.flinchless.to_string(),
+            self.disable_hitlag.to_string(),
+            self.direct.to_string(),
+            (self.target_situations.bits() as i32).to_string(),
+            (self.target_categories.bits() as i32).to_string(),
+            (self.target_parts.bits() as i32).to_string(),
+            self.friendly_fire.to_string(),
+            codegen::hash(self.collision_effect.as_hash().0),
+            (self.sound_level as i32).to_string(),
+            (self.sound as i32).to_string(),
+            (self.region as i32).to_string(),
+        ];
+
+        let mut code = String::from("macros::ATTACK(\n    agent,\n");
+        for arg in args {
+            code.push_str(&format!("    {arg},\n"));
+        }
+        code.push_str(");\n");
 
         if let Some(extra_hitstun) = self.extra_histun {
-            AttackModule::set_add_reaction_frame_revised(
-                context.module_accessor,
-                self.id as i32,
-                extra_hitstun,
-                false,
-            );
+            code.push_str(&format!(
+                "AttackModule::set_add_reaction_frame_revised(agent.module_accessor, {}, {}, false);\n",
+                self.id,
+                codegen::float(extra_hitstun)
+            ));
         }
 
         if let AttackAngle::Vector {
@@ -635,54 +718,367 @@ impl Action for Attack {
             num_frames,
         } = &self.angle
         {
-            AttackModule::set_vec_target_pos(
-                context.module_accessor,
+            code.push_str(&format!(
+                "AttackModule::set_vec_target_pos(agent.module_accessor, {}, {}, &Vector2f {{ x: {}, y: {} }}, {num_frames}, false);\n",
+                self.id,
+                codegen::hash(relative_to.0),
+                codegen::float(offset.x),
+                codegen::float(offset.y),
+            ));
+        }
+
+        Some(code)
+    }
+
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        let (knockback_growth, fixed_knockback, base_knockback) = self.knockback.values();
+        let (offset, offset2) = self.shape.values();
+        let damage = self.damage.evaluate(context);
+        let size = self.radius.evaluate(context);
+
+        context.attack(&AttackParams {
+            id: self.id,
+            part: self.part,
+            bone: self.bone.0,
+            damage,
+            angle: self.angle.as_angle() as u64,
+            knockback_growth,
+            fixed_knockback,
+            base_knockback,
+            size,
+            offset,
+            offset2,
+            hitlag_mul: self.hitlag_mul,
+            sdi_mul: self.sdi_strength_mul,
+            shield_setoff: self.shield_pushback as i32,
+            hit_direction: self.hit_direction as i32,
+            set_weight: self.set_weight,
+            shield_damage: self.shield_damage_value(),
+            trip_chance: self.trip_chance.unwrap_or(-1.0),
+            rehit_rate: self.rehit_rate.map(|rate| rate.get()).unwrap_or(0),
+            reflectable: self.reflectable,
+            absorbable: self.absorbable,
+            flinchless: self.flinchless,
+            disable_hitlag: self.disable_hitlag,
+            direct: self.direct,
+            situation_mask: self.target_situations.bits() as i32,
+            category_mask: self.target_categories.bits() as i32,
+            part_mask: self.target_parts.bits() as i32,
+            friendly_fire: self.friendly_fire,
+            collision_attribute: self.collision_effect.as_hash().0,
+            sound_level: self.sound_level as i32,
+            sound: self.sound as i32,
+            region: self.region as i32,
+        });
+
+        if let Some(extra_hitstun) = self.extra_histun {
+            context.set_add_reaction_frame_revised(self.id as i32, extra_hitstun);
+        }
+
+        if let AttackAngle::Vector {
+            relative_to,
+            offset,
+            num_frames,
+        } = &self.angle
+        {
+            context.set_vec_target_pos(
                 self.id as i32,
-                GameHash40::new_raw(relative_to.0 .0),
-                &smash::phx::Vector2f {
-                    x: offset.x,
-                    y: offset.y,
-                },
-                *num_frames as _,
-                false,
+                relative_to.0,
+                [offset.x, offset.y],
+                *num_frames as u32,
             );
         }
     }
 }
 
 decl_action!(
+    /// Removes the hitbox with the given id
     #[derive(Default)]
     struct AttackClear(i32);
 );
 
 impl Action for AttackClear {
     const NAME: &'static str = "Attack.clear";
+    const ACMD_NAME: &'static str = "AttackModule.clear";
 
-    #[cfg(feature = "bevy_reflect")]
-    unsafe fn execute(&self, context: &mut smash::lua2cpp::L2CAgentBase) {
-        unimplemented!()
+    fn metadata() -> ActionMetadata {
+        ActionMetadata::default().category(Category::Game)
     }
 
-    #[cfg(not(feature = "bevy_reflect"))]
-    unsafe fn execute(&self, context: &mut smash::lua2cpp::L2CAgentBase) {
-        AttackModule::clear(context.module_accessor, self.0, false);
+    fn to_rust(&self) -> Option<String> {
+        Some(format!(
+            "AttackModule::clear(agent.module_accessor, {}, false);",
+            self.0
+        ))
+    }
+
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        context.attack_clear(self.0);
     }
 }
 
 decl_action!(
+    /// Removes every hitbox
     #[derive(Default)]
     struct AttackClearAll;
 );
 impl Action for AttackClearAll {
     const NAME: &'static str = "Attack.clear_all";
+    const ACMD_NAME: &'static str = "AttackModule.clear_all";
+
+    fn metadata() -> ActionMetadata {
+        ActionMetadata::default().category(Category::Game)
+    }
+
+    fn to_rust(&self) -> Option<String> {
+        Some("AttackModule::clear_all(agent.module_accessor);".to_string())
+    }
+
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        context.attack_clear_all();
+    }
+}
+
+decl_action!(
+    /// Places a grab box, or replaces the one with the same id
+    pub struct Catch {
+        pub id: i32,
+        /// The bone the grab box follows
+        pub bone: SerdeHash40,
+        /// Written as a number or an expression
+        pub radius: Expr,
+        /// The offset of the grab box from its bone, or both ends of a capsule
+        pub shape: Shape,
+        /// The status the grabbed target is put in, such as `FIGHTER_STATUS_KIND_CAPTURE_PULLED`
+        pub status: LuaConst,
+        pub target_situations: SituationMask,
+    }
+);
+
+impl Default for Catch {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            bone: SerdeHash40::new("top"),
+            radius: Expr::from(3.0),
+            shape: Shape::Sphere(Vec3::default()),
+            status: LuaConst::new("FIGHTER_STATUS_KIND_CAPTURE_PULLED"),
+            target_situations: SituationMask::GROUND | SituationMask::AIR,
+        }
+    }
+}
+
+impl Action for Catch {
+    const NAME: &'static str = "Catch.set";
+    const ACMD_NAME: &'static str = "CATCH";
+
+    fn metadata() -> ActionMetadata {
+        ActionMetadata::default()
+            .category(Category::Game)
+            .defaults(&Self::default())
+    }
+
+    fn to_rust(&self) -> Option<String> {
+        let ([x, y, z], _) = self.shape.values();
+        let [x2, y2, z2] = self.shape.rust_offset2();
+
+        Some(format!(
+            "macros::CATCH(agent, {}, {}, {}, {}, {}, {}, {x2}, {y2}, {z2}, {}, {});",
+            self.id,
+            codegen::hash(self.bone.0),
+            self.radius.to_rust(),
+            codegen::float(x),
+            codegen::float(y),
+            codegen::float(z),
+            self.status.to_rust(),
+            self.target_situations.bits() as i32
+        ))
+    }
+
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        let (offset, offset2) = self.shape.values();
+        let size = self.radius.evaluate(context);
+
+        context.catch(&CatchParams {
+            id: self.id,
+            bone: self.bone.0,
+            size,
+            offset,
+            offset2,
+            status: self.status.value,
+            situation_mask: self.target_situations.bits() as i32,
+        });
+    }
+}
+
+decl_action!(
+    /// Removes every grab box
+    #[derive(Default)]
+    pub struct CatchClearAll;
+);
+
+impl Action for CatchClearAll {
+    const NAME: &'static str = "Catch.clear_all";
+    const ACMD_NAME: &'static str = "CatchModule.clear_all";
+
+    fn metadata() -> ActionMetadata {
+        ActionMetadata::default().category(Category::Game)
+    }
+
+    fn to_rust(&self) -> Option<String> {
+        Some("CatchModule::clear_all(agent.module_accessor);".to_string())
+    }
+
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        context.catch_clear_all();
+    }
+}
+
+decl_action!(
+    /// Sets the hit a throw deals to the grabbed target. Nothing happens until `Attack.hit_abs`
+    /// deals it, and the target is usually released by the throw's status on the frame after.
+    pub struct AttackAbs {
+        /// Such as `FIGHTER_ATTACK_ABSOLUTE_KIND_THROW` or `FIGHTER_ATTACK_ABSOLUTE_KIND_CATCH`
+        pub kind: LuaConst,
+        pub id: i32,
+        /// The damage dealt, written as a number or an expression
+        pub damage: Expr,
+        pub angle: AttackAngle,
+        pub knockback: Knockback,
+        /// How the direction the target is sent in is decided
+        pub hit_direction: HitDirection,
+        /// The effect of the hit, such as fire or sleep
+        pub collision_effect: CollisionAttribute,
+        pub sound_level: SoundLevel,
+        pub sound: Sound,
+        pub region: AttackRegion,
+    }
+);
+
+impl Default for AttackAbs {
+    fn default() -> Self {
+        Self {
+            kind: LuaConst::new("FIGHTER_ATTACK_ABSOLUTE_KIND_THROW"),
+            id: 0,
+            damage: Expr::from(8.0),
+            angle: AttackAngle::Normal(45),
+            knockback: Knockback::Scaling {
+                growth: 60,
+                base: 70,
+            },
+            hit_direction: HitDirection::Forward,
+            collision_effect: CollisionAttribute::Normal,
+            sound_level: SoundLevel::Small,
+            sound: Sound::None,
+            region: AttackRegion::Throw,
+        }
+    }
+}
+
+impl Action for AttackAbs {
+    const NAME: &'static str = "Attack.set_abs";
+    const ACMD_NAME: &'static str = "ATTACK_ABS";
+
+    fn metadata() -> ActionMetadata {
+        ActionMetadata::default()
+            .category(Category::Game)
+            .defaults(&Self::default())
+    }
+
+    fn to_rust(&self) -> Option<String> {
+        let (kbg, fkb, bkb) = self.knockback.values();
+        let (arg8, arg9) = AttackAbsParams::AFTER_KNOCKBACK;
+        let (arg11, arg12) = AttackAbsParams::AFTER_HIT_DIRECTION;
+
+        let args = [
+            self.kind.to_rust(),
+            self.id.to_string(),
+            self.damage.to_rust(),
+            self.angle.as_angle().to_string(),
+            kbg.to_string(),
+            fkb.to_string(),
+            bkb.to_string(),
+            codegen::float(arg8),
+            codegen::float(arg9),
+            (self.hit_direction as i32).to_string(),
+            codegen::float(arg11),
+            arg12.to_string(),
+            codegen::hash(self.collision_effect.as_hash().0),
+            (self.sound_level as i32).to_string(),
+            (self.sound as i32).to_string(),
+            (self.region as i32).to_string(),
+        ];
+
+        Some(format!("macros::ATTACK_ABS(agent, {});", args.join(", ")))
+    }
+
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        let (knockback_growth, fixed_knockback, base_knockback) = self.knockback.values();
+        let damage = self.damage.evaluate(context);
+
+        context.attack_abs(&AttackAbsParams {
+            kind: self.kind.value,
+            id: self.id,
+            damage,
+            angle: self.angle.as_angle() as u64,
+            knockback_growth,
+            fixed_knockback,
+            base_knockback,
+            hit_direction: self.hit_direction as i32,
+            collision_attribute: self.collision_effect.as_hash().0,
+            sound_level: self.sound_level as i32,
+            sound: self.sound as i32,
+            region: self.region as i32,
+        });
+    }
+}
+
+decl_action!(
+    /// Deals the hit set with `Attack.set_abs` to the target being thrown
+    pub struct AttackHitAbs {
+        /// The kind the hit was set with
+        pub kind: LuaConst,
+        /// The bone the target is held at, usually `throw`
+        pub bone: SerdeHash40,
+    }
+);
+
+impl Default for AttackHitAbs {
+    fn default() -> Self {
+        Self {
+            kind: LuaConst::new("FIGHTER_ATTACK_ABSOLUTE_KIND_THROW"),
+            bone: SerdeHash40::new("throw"),
+        }
+    }
+}
+
+impl Action for AttackHitAbs {
+    const NAME: &'static str = "Attack.hit_abs";
+    const ACMD_NAME: &'static str = "ATK_HIT_ABS";
+
+    fn metadata() -> ActionMetadata {
+        ActionMetadata::default()
+            .category(Category::Game)
+            .defaults(&Self::default())
+    }
+
+    fn to_rust(&self) -> Option<String> {
+        let target = |variable: &str| {
+            format!("    WorkModule::get_int64(agent.module_accessor, *{variable}),\n")
+        };
+
+        let mut code = String::from("macros::ATK_HIT_ABS(\n    agent,\n");
+        code.push_str(&format!("    {},\n", self.kind.to_rust()));
+        code.push_str(&format!("    {},\n", codegen::hash(self.bone.0)));
+        code.push_str(&target("FIGHTER_STATUS_THROW_WORK_INT_TARGET_OBJECT"));
+        code.push_str(&target("FIGHTER_STATUS_THROW_WORK_INT_TARGET_HIT_GROUP"));
+        code.push_str(&target("FIGHTER_STATUS_THROW_WORK_INT_TARGET_HIT_NO"));
+        code.push_str(");\n");
 
-    #[cfg(feature = "bevy_reflect")]
-    unsafe fn execute(&self, context: &mut smash::lua2cpp::L2CAgentBase) {
-        unimplemented!()
+        Some(code)
     }
 
-    #[cfg(not(feature = "bevy_reflect"))]
-    unsafe fn execute(&self, context: &mut smash::lua2cpp::L2CAgentBase) {
-        AttackModule::clear_all(context.module_accessor);
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        context.atk_hit_abs(self.kind.value, self.bone.0);
     }
 }
@@ -0,0 +1,398 @@
+use acmd_engine::{
+    action::{ActionBlock, ActionError, ActionRegistry, ControlAction},
+    codegen,
+    context::ExecutionContext,
+    schema::action_list,
+};
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::lua_const::LuaConst;
+
+#[derive(Deserialize, Serialize, JsonSchema, Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum Comparison {
+    #[default]
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+impl Comparison {
+    pub fn operator(&self) -> &'static str {
+        match self {
+            Self::Equal => "==",
+            Self::NotEqual => "!=",
+            Self::Less => "<",
+            Self::LessEqual => "<=",
+            Self::Greater => ">",
+            Self::GreaterEqual => ">=",
+        }
+    }
+
+    pub fn compare<T: PartialOrd>(&self, lhs: T, rhs: T) -> bool {
+        match self {
+            Self::Equal => lhs == rhs,
+            Self::NotEqual => lhs != rhs,
+            Self::Less => lhs < rhs,
+            Self::LessEqual => lhs <= rhs,
+            Self::Greater => lhs > rhs,
+            Self::GreaterEqual => lhs >= rhs,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Debug, Clone, Default)]
+pub enum Condition {
+    /// Equivalent to `is_excute` in ACMD, true when the script isn't being run to search for frames
+    #[default]
+    IsExcute,
+    Flag(LuaConst),
+    Int {
+        variable: LuaConst,
+        compare: Comparison,
+        value: i32,
+    },
+    Float {
+        variable: LuaConst,
+        compare: Comparison,
+        value: f32,
+    },
+    Not(Box<Condition>),
+    All(Vec<Condition>),
+    Any(Vec<Condition>),
+}
+
+impl Condition {
+    pub fn evaluate(&self, context: &mut dyn ExecutionContext) -> bool {
+        match self {
+            Self::IsExcute => context.is_excute(),
+            Self::Flag(flag) => context.is_flag(flag.value),
+            Self::Int {
+                variable,
+                compare,
+                value,
+            } => compare.compare(context.get_int(variable.value), *value),
+            Self::Float {
+                variable,
+                compare,
+                value,
+            } => compare.compare(context.get_float(variable.value), *value),
+            Self::Not(condition) => !condition.evaluate(context),
+            Self::All(conditions) => conditions.iter().all(|c| c.evaluate(context)),
+            Self::Any(conditions) => conditions.iter().any(|c| c.evaluate(context)),
+        }
+    }
+
+    /// The condition as a Rust expression, see [`acmd_engine::codegen`]
+    pub fn to_rust(&self) -> String {
+        let join = |conditions: &[Condition], operator: &str, empty: &str| {
+            if conditions.is_empty() {
+                return empty.to_string();
+            }
+
+            let conditions: Vec<_> = conditions.iter().map(Self::operand).collect();
+            conditions.join(operator)
+        };
+
+        match self {
+            Self::IsExcute => "macros::is_excute(agent)".to_string(),
+            Self::Flag(flag) => format!(
+                "WorkModule::is_flag(agent.module_accessor, {})",
+                flag.to_rust()
+            ),
+            Self::Int {
+                variable,
+                compare,
+                value,
+            } => format!(
+                "WorkModule::get_int(agent.module_accessor, {}) {} {value}",
+                variable.to_rust(),
+                compare.operator()
+            ),
+            Self::Float {
+                variable,
+                compare,
+                value,
+            } => format!(
+                "WorkModule::get_float(agent.module_accessor, {}) {} {}",
+                variable.to_rust(),
+                compare.operator(),
+                codegen::float(*value)
+            ),
+            Self::Not(condition) => format!("!{}", condition.operand()),
+            Self::All(conditions) => join(conditions, " && ", "true"),
+            Self::Any(conditions) => join(conditions, " || ", "false"),
+        }
+    }
+
+    /// The condition as an operand of `!`, `&&` or `||`, in parentheses unless it is a single call
+    fn operand(&self) -> String {
+        match self {
+            Self::Int { .. } | Self::Float { .. } => format!("({})", self.to_rust()),
+            Self::All(conditions) | Self::Any(conditions) if conditions.len() > 1 => {
+                format!("({})", self.to_rust())
+            }
+            _ => self.to_rust(),
+        }
+    }
+}
+
+fn parse_repr<T: DeserializeOwned>(name: &'static str, value: Value) -> Result<T, ActionError> {
+    serde_json::from_value(value).map_err(|error| ActionError::FromValueError { name, error })
+}
+
+fn write_repr<T: Serialize>(name: &'static str, repr: T) -> Result<Value, ActionError> {
+    serde_json::to_value(repr).map_err(|error| ActionError::IntoValueError { name, error })
+}
+
+/// Runs `then` if the condition holds, and `else` otherwise
+#[derive(Deserialize, Serialize, JsonSchema)]
+#[schemars(rename = "If")]
+struct IfRepr {
+    condition: Condition,
+    #[schemars(schema_with = "action_list")]
+    then: Vec<Value>,
+    #[serde(default, rename = "else", skip_serializing_if = "Vec::is_empty")]
+    #[schemars(schema_with = "action_list")]
+    otherwise: Vec<Value>,
+}
+
+#[derive(Default)]
+pub struct If {
+    pub condition: Condition,
+    pub then: ActionBlock,
+    pub otherwise: ActionBlock,
+}
+
+impl ControlAction for If {
+    const NAME: &'static str = "If";
+
+    fn from_value(registry: &ActionRegistry, value: Value) -> Result<Self, ActionError> {
+        let repr: IfRepr = parse_repr(Self::NAME, value)?;
+        let then = ActionBlock::from_values(registry, repr.then).map_err(|e| e.at_key("then"));
+        let otherwise =
+            ActionBlock::from_values(registry, repr.otherwise).map_err(|e| e.at_key("else"));
+
+        match (then, otherwise) {
+            (Ok(then), Ok(otherwise)) => Ok(Self {
+                condition: repr.condition,
+                then,
+                otherwise,
+            }),
+            (then, otherwise) => Err(ActionError::from_errors(
+                then.err().into_iter().chain(otherwise.err()).collect(),
+            )
+            .unwrap()),
+        }
+    }
+
+    fn into_value(&self, registry: &ActionRegistry) -> Result<Value, ActionError> {
+        write_repr(
+            Self::NAME,
+            IfRepr {
+                condition: self.condition.clone(),
+                then: self.then.to_values(registry)?,
+                otherwise: self.otherwise.to_values(registry)?,
+            },
+        )
+    }
+
+    unsafe fn execute(
+        &self,
+        registry: &ActionRegistry,
+        context: &mut dyn ExecutionContext,
+    ) -> Result<(), ActionError> {
+        if self.condition.evaluate(context) {
+            self.then.execute(registry, context)
+        } else {
+            self.otherwise.execute(registry, context)
+        }
+    }
+
+    fn blocks(&self) -> Vec<&ActionBlock> {
+        vec![&self.then, &self.otherwise]
+    }
+
+    fn always_waits(&self, registry: &ActionRegistry) -> bool {
+        self.then.always_waits(registry) && self.otherwise.always_waits(registry)
+    }
+
+    fn schema(generator: &mut SchemaGenerator) -> Schema {
+        generator.subschema_for::<IfRepr>()
+    }
+
+    fn to_rust(&self, registry: &ActionRegistry) -> Option<String> {
+        let mut code = format!(
+            "if {} {{\n{}}}",
+            self.condition.to_rust(),
+            codegen::block(registry, &self.then)
+        );
+
+        if !self.otherwise.is_empty() {
+            code.push_str(&format!(
+                " else {{\n{}}}",
+                codegen::block(registry, &self.otherwise)
+            ));
+        }
+
+        Some(code)
+    }
+}
+
+/// Runs the nested actions `count` times
+#[derive(Deserialize, Serialize, JsonSchema)]
+#[schemars(rename = "Repeat")]
+struct RepeatRepr {
+    count: u32,
+    #[schemars(schema_with = "action_list")]
+    actions: Vec<Value>,
+}
+
+#[derive(Default)]
+pub struct Repeat {
+    pub count: u32,
+    pub actions: ActionBlock,
+}
+
+impl ControlAction for Repeat {
+    const NAME: &'static str = "Repeat";
+
+    fn from_value(registry: &ActionRegistry, value: Value) -> Result<Self, ActionError> {
+        let repr: RepeatRepr = parse_repr(Self::NAME, value)?;
+        Ok(Self {
+            count: repr.count,
+            actions: ActionBlock::from_values(registry, repr.actions)
+                .map_err(|e| e.at_key("actions"))?,
+        })
+    }
+
+    fn into_value(&self, registry: &ActionRegistry) -> Result<Value, ActionError> {
+        write_repr(
+            Self::NAME,
+            RepeatRepr {
+                count: self.count,
+                actions: self.actions.to_values(registry)?,
+            },
+        )
+    }
+
+    unsafe fn execute(
+        &self,
+        registry: &ActionRegistry,
+        context: &mut dyn ExecutionContext,
+    ) -> Result<(), ActionError> {
+        for _ in 0..self.count {
+            self.actions.execute(registry, context)?;
+        }
+
+        Ok(())
+    }
+
+    fn blocks(&self) -> Vec<&ActionBlock> {
+        vec![&self.actions]
+    }
+
+    fn always_waits(&self, registry: &ActionRegistry) -> bool {
+        self.count > 0 && self.actions.always_waits(registry)
+    }
+
+    fn schema(generator: &mut SchemaGenerator) -> Schema {
+        generator.subschema_for::<RepeatRepr>()
+    }
+
+    fn to_rust(&self, registry: &ActionRegistry) -> Option<String> {
+        Some(format!(
+            "for _ in 0..{} {{\n{}}}",
+            self.count,
+            codegen::block(registry, &self.actions)
+        ))
+    }
+}
+
+/// Repeats the nested actions while the condition is true
+#[derive(Deserialize, Serialize, JsonSchema)]
+#[schemars(rename = "While")]
+struct WhileRepr {
+    condition: Condition,
+    #[schemars(schema_with = "action_list")]
+    actions: Vec<Value>,
+}
+
+/// Runs the nested actions for as long as the condition holds.
+///
+/// Every run of the body has to wait for at least one frame, otherwise the loop could never yield
+/// back to the game, so a body that doesn't is rejected when the script is loaded. Waits in both
+/// branches of an `If` count, waits inside of a nested `While` or of a called script don't, since
+/// calls are only linked once every script is loaded.
+#[derive(Default)]
+pub struct While {
+    pub condition: Condition,
+    pub actions: ActionBlock,
+}
+
+impl ControlAction for While {
+    const NAME: &'static str = "While";
+
+    fn from_value(registry: &ActionRegistry, value: Value) -> Result<Self, ActionError> {
+        let repr: WhileRepr = parse_repr(Self::NAME, value)?;
+        let actions =
+            ActionBlock::from_values(registry, repr.actions).map_err(|e| e.at_key("actions"))?;
+
+        if !actions.always_waits(registry) {
+            return Err(ActionError::FromValueError {
+                name: Self::NAME,
+                error: serde::de::Error::custom(
+                    "the actions don't always wait, so the loop could never give control back to the game",
+                ),
+            }
+            .at_key("actions"));
+        }
+
+        Ok(Self {
+            condition: repr.condition,
+            actions,
+        })
+    }
+
+    fn into_value(&self, registry: &ActionRegistry) -> Result<Value, ActionError> {
+        write_repr(
+            Self::NAME,
+            WhileRepr {
+                condition: self.condition.clone(),
+                actions: self.actions.to_values(registry)?,
+            },
+        )
+    }
+
+    unsafe fn execute(
+        &self,
+        registry: &ActionRegistry,
+        context: &mut dyn ExecutionContext,
+    ) -> Result<(), ActionError> {
+        while self.condition.evaluate(context) {
+            self.actions.execute(registry, context)?;
+        }
+
+        Ok(())
+    }
+
+    fn blocks(&self) -> Vec<&ActionBlock> {
+        vec![&self.actions]
+    }
+
+    fn schema(generator: &mut SchemaGenerator) -> Schema {
+        generator.subschema_for::<WhileRepr>()
+    }
+
+    fn to_rust(&self, registry: &ActionRegistry) -> Option<String> {
+        Some(format!(
+            "while {} {{\n{}}}",
+            self.condition.to_rust(),
+            codegen::block(registry, &self.actions)
+        ))
+    }
+}
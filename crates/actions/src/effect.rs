@@ -0,0 +1,308 @@
+use acmd_engine::{
+    action::Action,
+    asset::Category,
+    codegen,
+    context::{EffectFollowParams, EffectParams, ExecutionContext},
+    metadata::ActionMetadata,
+};
+use glam::Vec3;
+
+#[cfg(feature = "bevy_reflect")]
+use bevy_reflect::prelude::*;
+
+use crate::{decl_action, SerdeHash40};
+
+fn vec3(value: Vec3) -> [String; 3] {
+    value.to_array().map(codegen::float)
+}
+
+decl_action!(
+    /// Spawns an effect at a bone, which stays where it was spawned
+    pub struct Effect {
+        /// The effect to spawn, such as `sys_attack_arc`
+        pub effect: SerdeHash40,
+        /// The bone the effect is spawned at
+        pub bone: SerdeHash40,
+        #[schemars(with = "[f32; 3]")]
+        pub offset: Vec3,
+        /// The rotation around each axis, in degrees
+        #[schemars(with = "[f32; 3]")]
+        pub rotation: Vec3,
+        pub size: f32,
+        /// How far the offset is randomly moved in each direction
+        #[serde(default)]
+        #[schemars(with = "[f32; 3]")]
+        pub offset_range: Vec3,
+        /// How far the rotation is randomly moved around each axis, in degrees
+        #[serde(default)]
+        #[schemars(with = "[f32; 3]")]
+        pub rotation_range: Vec3,
+        /// Removes the effect when the animation changes
+        pub terminate: bool,
+    }
+);
+
+impl Default for Effect {
+    fn default() -> Self {
+        Self {
+            effect: SerdeHash40::default(),
+            bone: SerdeHash40::new("top"),
+            offset: Vec3::ZERO,
+            rotation: Vec3::ZERO,
+            size: 1.0,
+            offset_range: Vec3::ZERO,
+            rotation_range: Vec3::ZERO,
+            terminate: true,
+        }
+    }
+}
+
+impl Effect {
+    fn params(&self) -> EffectParams {
+        EffectParams {
+            effect: self.effect.0,
+            bone: self.bone.0,
+            offset: self.offset.to_array(),
+            rotation: self.rotation.to_array(),
+            size: self.size,
+            offset_range: self.offset_range.to_array(),
+            rotation_range: self.rotation_range.to_array(),
+            terminate: self.terminate,
+        }
+    }
+
+    /// The arguments of `EFFECT` and `LANDING_EFFECT` after the agent
+    fn rust_args(&self) -> String {
+        let mut args = vec![codegen::hash(self.effect.0), codegen::hash(self.bone.0)];
+        args.extend(vec3(self.offset));
+        args.extend(vec3(self.rotation));
+        args.push(codegen::float(self.size));
+        args.extend(vec3(self.offset_range));
+        args.extend(vec3(self.rotation_range));
+        args.push(self.terminate.to_string());
+        args.join(", ")
+    }
+}
+
+impl Action for Effect {
+    const NAME: &'static str = "Effect.spawn";
+    const ACMD_NAME: &'static str = "EFFECT";
+
+    fn metadata() -> ActionMetadata {
+        ActionMetadata::default()
+            .category(Category::Effect)
+            .defaults(&Self::default())
+    }
+
+    fn to_rust(&self) -> Option<String> {
+        Some(format!("macros::EFFECT(agent, {});", self.rust_args()))
+    }
+
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        context.effect(&self.params());
+    }
+}
+
+decl_action!(
+    /// Spawns an effect at a bone which moves along with it
+    pub struct EffectFollow {
+        pub effect: SerdeHash40,
+        pub bone: SerdeHash40,
+        #[schemars(with = "[f32; 3]")]
+        pub offset: Vec3,
+        /// The rotation around each axis, in degrees
+        #[schemars(with = "[f32; 3]")]
+        pub rotation: Vec3,
+        pub size: f32,
+        /// Removes the effect when the animation changes
+        pub terminate: bool,
+    }
+);
+
+impl Default for EffectFollow {
+    fn default() -> Self {
+        Self {
+            effect: SerdeHash40::default(),
+            bone: SerdeHash40::new("top"),
+            offset: Vec3::ZERO,
+            rotation: Vec3::ZERO,
+            size: 1.0,
+            terminate: true,
+        }
+    }
+}
+
+impl Action for EffectFollow {
+    const NAME: &'static str = "Effect.follow";
+    const ACMD_NAME: &'static str = "EFFECT_FOLLOW";
+
+    fn metadata() -> ActionMetadata {
+        ActionMetadata::default()
+            .category(Category::Effect)
+            .defaults(&Self::default())
+    }
+
+    fn to_rust(&self) -> Option<String> {
+        let mut args = vec![codegen::hash(self.effect.0), codegen::hash(self.bone.0)];
+        args.extend(vec3(self.offset));
+        args.extend(vec3(self.rotation));
+        args.push(codegen::float(self.size));
+        args.push(self.terminate.to_string());
+
+        Some(format!(
+            "macros::EFFECT_FOLLOW(agent, {});",
+            args.join(", ")
+        ))
+    }
+
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        context.effect_follow(&EffectFollowParams {
+            effect: self.effect.0,
+            bone: self.bone.0,
+            offset: self.offset.to_array(),
+            rotation: self.rotation.to_array(),
+            size: self.size,
+            terminate: self.terminate,
+        });
+    }
+}
+
+decl_action!(
+    /// Spawns an effect on the ground below a bone, such as the dust of a landing. Takes the same
+    /// fields as `Effect.spawn`.
+    #[derive(Default)]
+    pub struct LandingEffect(pub Effect);
+);
+
+impl Action for LandingEffect {
+    const NAME: &'static str = "Effect.landing";
+    const ACMD_NAME: &'static str = "LANDING_EFFECT";
+
+    fn metadata() -> ActionMetadata {
+        ActionMetadata::default()
+            .category(Category::Effect)
+            .defaults(&Self::default())
+    }
+
+    fn to_rust(&self) -> Option<String> {
+        Some(format!(
+            "macros::LANDING_EFFECT(agent, {});",
+            self.0.rust_args()
+        ))
+    }
+
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        context.landing_effect(&self.0.params());
+    }
+}
+
+decl_action!(
+    /// Tints the effect spawned last, with each channel usually from 0.0 to 1.0
+    pub struct LastEffectSetColor(#[schemars(with = "[f32; 3]")] pub Vec3);
+);
+
+impl Default for LastEffectSetColor {
+    fn default() -> Self {
+        Self(Vec3::ONE)
+    }
+}
+
+impl Action for LastEffectSetColor {
+    const NAME: &'static str = "Effect.set_last_color";
+    const ACMD_NAME: &'static str = "LAST_EFFECT_SET_COLOR";
+
+    fn metadata() -> ActionMetadata {
+        ActionMetadata::default()
+            .category(Category::Effect)
+            .defaults(&Self::default())
+    }
+
+    fn to_rust(&self) -> Option<String> {
+        Some(format!(
+            "macros::LAST_EFFECT_SET_COLOR(agent, {});",
+            vec3(self.0).join(", ")
+        ))
+    }
+
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        context.last_effect_set_color(self.0.to_array());
+    }
+}
+
+decl_action!(
+    /// Changes how fast the effect spawned last plays
+    #[derive(Copy)]
+    pub struct LastEffectSetRate(pub f32);
+);
+
+impl Default for LastEffectSetRate {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+impl Action for LastEffectSetRate {
+    const NAME: &'static str = "Effect.set_last_rate";
+    const ACMD_NAME: &'static str = "LAST_EFFECT_SET_RATE";
+
+    fn metadata() -> ActionMetadata {
+        ActionMetadata::default()
+            .category(Category::Effect)
+            .defaults(&Self::default())
+    }
+
+    fn to_rust(&self) -> Option<String> {
+        Some(format!(
+            "macros::LAST_EFFECT_SET_RATE(agent, {});",
+            codegen::float(self.0)
+        ))
+    }
+
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        context.last_effect_set_rate(self.0);
+    }
+}
+
+decl_action!(
+    /// Removes every effect of a kind that the agent spawned
+    pub struct EffectOffKind {
+        pub effect: SerdeHash40,
+        /// Lets the particles already spawned play out instead of removing them
+        pub deactivate: bool,
+        pub immediate: bool,
+    }
+);
+
+impl Default for EffectOffKind {
+    fn default() -> Self {
+        Self {
+            effect: SerdeHash40::default(),
+            deactivate: false,
+            immediate: true,
+        }
+    }
+}
+
+impl Action for EffectOffKind {
+    const NAME: &'static str = "Effect.off_kind";
+    const ACMD_NAME: &'static str = "EFFECT_OFF_KIND";
+
+    fn metadata() -> ActionMetadata {
+        ActionMetadata::default()
+            .category(Category::Effect)
+            .defaults(&Self::default())
+    }
+
+    fn to_rust(&self) -> Option<String> {
+        Some(format!(
+            "macros::EFFECT_OFF_KIND(agent, {}, {}, {});",
+            codegen::hash(self.effect.0),
+            self.deactivate,
+            self.immediate
+        ))
+    }
+
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        context.effect_off_kind(self.effect.0, self.deactivate, self.immediate);
+    }
+}
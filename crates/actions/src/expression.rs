@@ -0,0 +1,169 @@
+use acmd_engine::{
+    action::Action, asset::Category, codegen, context::ExecutionContext, metadata::ActionMetadata,
+};
+
+#[cfg(feature = "bevy_reflect")]
+use bevy_reflect::prelude::*;
+
+use crate::{decl_action, lua_const::LuaConst, SerdeHash40};
+
+decl_action!(
+    /// Rumbles the controller of the player the agent belongs to
+    pub struct Rumble {
+        /// The rumble pattern, such as `rbkind_nohitm`
+        pub kind: SerdeHash40,
+        #[serde(default)]
+        pub frames: i32,
+        /// Repeats the pattern until another rumble replaces it
+        #[serde(default)]
+        pub looping: bool,
+    }
+);
+
+impl Default for Rumble {
+    fn default() -> Self {
+        Self {
+            kind: SerdeHash40::new("rbkind_nohitm"),
+            frames: 0,
+            looping: false,
+        }
+    }
+}
+
+decl_action!(
+    /// Changes how the agent is tilted to match the ground it stands on
+    #[derive(Default)]
+    pub struct Slope {
+        /// Such as `MA_MSC_CMD_SLOPE_SLOPE` or `MA_MSC_CMD_SLOPE_SLOPE_INTP`
+        pub kind: LuaConst,
+        /// Such as `SLOPE_STATUS_LR` or `SLOPE_STATUS_TOP`
+        pub status: LuaConst,
+    }
+);
+
+decl_action!(
+    /// Shows or hides the agent's whole model
+    #[derive(Copy, Default)]
+    pub struct SetVisibleWhole(pub bool);
+);
+
+decl_action!(
+    /// Switches one of the model's mesh groups to another state, such as an open or closed hand
+    #[derive(Copy, Default)]
+    pub struct SetVisibility {
+        pub group: SerdeHash40,
+        pub state: SerdeHash40,
+    }
+);
+
+decl_action!(
+    /// Shows or hides the item the agent is holding
+    #[derive(Copy, Default)]
+    pub struct SetHaveItemVisibility(pub bool);
+);
+
+impl Action for Rumble {
+    const NAME: &'static str = "Control.set_rumble";
+    const ACMD_NAME: &'static str = "ControlModule.set_rumble";
+
+    fn metadata() -> ActionMetadata {
+        ActionMetadata::default()
+            .category(Category::Expression)
+            .defaults(&Self::default())
+    }
+
+    fn to_rust(&self) -> Option<String> {
+        Some(format!(
+            "ControlModule::set_rumble(agent.module_accessor, {}, {}, {}, *BATTLE_OBJECT_ID_INVALID as u32);",
+            codegen::hash(self.kind.0),
+            self.frames,
+            self.looping
+        ))
+    }
+
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        context.set_rumble(self.kind.0, self.frames, self.looping);
+    }
+}
+
+impl Action for Slope {
+    const NAME: &'static str = "Slope";
+    const ACMD_NAME: &'static str = "slope";
+
+    fn metadata() -> ActionMetadata {
+        ActionMetadata::default().category(Category::Expression)
+    }
+
+    fn to_rust(&self) -> Option<String> {
+        Some(format!(
+            "slope!(agent, {}, {});",
+            self.kind.to_rust(),
+            self.status.to_rust()
+        ))
+    }
+
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        context.slope(self.kind.value, self.status.value);
+    }
+}
+
+impl Action for SetVisibleWhole {
+    const NAME: &'static str = "Visibility.set_whole";
+    const ACMD_NAME: &'static str = "VisibilityModule.set_whole";
+
+    fn metadata() -> ActionMetadata {
+        ActionMetadata::default().category(Category::Expression)
+    }
+
+    fn to_rust(&self) -> Option<String> {
+        Some(format!(
+            "VisibilityModule::set_whole(agent.module_accessor, {});",
+            self.0
+        ))
+    }
+
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        context.set_visibility_whole(self.0);
+    }
+}
+
+impl Action for SetVisibility {
+    const NAME: &'static str = "Visibility.set";
+    const ACMD_NAME: &'static str = "VisibilityModule.set_int64";
+
+    fn metadata() -> ActionMetadata {
+        ActionMetadata::default().category(Category::Expression)
+    }
+
+    fn to_rust(&self) -> Option<String> {
+        Some(format!(
+            "VisibilityModule::set_int64(agent.module_accessor, {}.hash as i64, {}.hash as i64);",
+            codegen::hash(self.group.0),
+            codegen::hash(self.state.0)
+        ))
+    }
+
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        context.set_visibility(self.group.0, self.state.0);
+    }
+}
+
+impl Action for SetHaveItemVisibility {
+    const NAME: &'static str = "Item.set_have_item_visibility";
+    const ACMD_NAME: &'static str = "ItemModule.set_have_item_visibility";
+
+    fn metadata() -> ActionMetadata {
+        ActionMetadata::default().category(Category::Expression)
+    }
+
+    fn to_rust(&self) -> Option<String> {
+        Some(format!(
+            "ItemModule::set_have_item_visibility(agent.module_accessor, {}, 0);",
+            self.0
+        ))
+    }
+
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        context.set_have_item_visibility(self.0);
+    }
+}
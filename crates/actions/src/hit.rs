@@ -0,0 +1,302 @@
+use acmd_engine::{
+    action::Action,
+    asset::Category,
+    codegen,
+    context::{self, ExecutionContext},
+    metadata::ActionMetadata,
+};
+
+#[cfg(feature = "bevy_reflect")]
+use bevy_reflect::prelude::*;
+
+use crate::{decl_action, lua_const::LuaConst, SerdeHash40};
+
+decl_action!(
+    /// The state of a hurtbox, which decides what happens when an attack overlaps it
+    #[derive(Copy, Default)]
+    pub enum HitStatus {
+        #[default]
+        Normal,
+        /// Intangible, attacks pass through it without hitting
+        Xlu,
+        /// Attacks hit it without dealing damage or knockback
+        Invincible,
+        /// The hurtbox is turned off
+        Off,
+    }
+);
+
+impl HitStatus {
+    /// The lua constant of the status, such as `HIT_STATUS_XLU`
+    const fn lua_const(&self) -> &'static str {
+        match self {
+            Self::Normal => "HIT_STATUS_NORMAL",
+            Self::Xlu => "HIT_STATUS_XLU",
+            Self::Invincible => "HIT_STATUS_INVINCIBLE",
+            Self::Off => "HIT_STATUS_OFF",
+        }
+    }
+}
+
+impl From<HitStatus> for context::HitStatus {
+    fn from(status: HitStatus) -> Self {
+        match status {
+            HitStatus::Normal => Self::Normal,
+            HitStatus::Xlu => Self::Xlu,
+            HitStatus::Invincible => Self::Invincible,
+            HitStatus::Off => Self::Off,
+        }
+    }
+}
+
+decl_action!(
+    /// Sets the status of every hurtbox
+    #[derive(Copy, Default)]
+    pub struct SetHitStatusAll(pub HitStatus);
+);
+
+impl Action for SetHitStatusAll {
+    const NAME: &'static str = "Hit.set_status_all";
+    const ACMD_NAME: &'static str = "HitModule.set_status_all";
+
+    fn metadata() -> ActionMetadata {
+        ActionMetadata::default().category(Category::Game)
+    }
+
+    fn to_rust(&self) -> Option<String> {
+        Some(format!(
+            "HitModule::set_status_all(agent.module_accessor, HitStatus(*{}), 0);",
+            self.0.lua_const()
+        ))
+    }
+
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        context.set_hit_status_all(self.0.into());
+    }
+}
+
+decl_action!(
+    /// Sets the status of the whole agent, which takes priority over the status of each hurtbox.
+    /// Set it back to `Normal` to use the status of each hurtbox again.
+    #[derive(Copy, Default)]
+    pub struct SetHitWhole(pub HitStatus);
+);
+
+impl Action for SetHitWhole {
+    const NAME: &'static str = "Hit.set_whole";
+    const ACMD_NAME: &'static str = "HitModule.set_whole";
+
+    fn metadata() -> ActionMetadata {
+        ActionMetadata::default().category(Category::Game)
+    }
+
+    fn to_rust(&self) -> Option<String> {
+        Some(format!(
+            "HitModule::set_whole(agent.module_accessor, HitStatus(*{}), 0);",
+            self.0.lua_const()
+        ))
+    }
+
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        context.set_hit_whole(self.0.into());
+    }
+}
+
+decl_action!(
+    /// Sets the status of the hurtbox on a bone, such as making the head intangible
+    #[derive(Copy)]
+    pub struct HitNode {
+        pub bone: SerdeHash40,
+        pub status: HitStatus,
+    }
+);
+
+impl Default for HitNode {
+    fn default() -> Self {
+        Self {
+            bone: SerdeHash40::new("head"),
+            status: HitStatus::Xlu,
+        }
+    }
+}
+
+impl Action for HitNode {
+    const NAME: &'static str = "Hit.set_node";
+    const ACMD_NAME: &'static str = "HIT_NODE";
+
+    fn metadata() -> ActionMetadata {
+        ActionMetadata::default()
+            .category(Category::Game)
+            .defaults(&Self::default())
+    }
+
+    fn to_rust(&self) -> Option<String> {
+        Some(format!(
+            "macros::HIT_NODE(agent, {}, *{});",
+            codegen::hash(self.bone.0),
+            self.status.lua_const()
+        ))
+    }
+
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        context.hit_node(self.bone.0, self.status.into());
+    }
+}
+
+decl_action!(
+    /// Sets the status of a hurtbox by its index in the fighter's hurtbox params
+    #[derive(Copy)]
+    pub struct HitNo {
+        pub part: i32,
+        pub status: HitStatus,
+    }
+);
+
+impl Default for HitNo {
+    fn default() -> Self {
+        Self {
+            part: 0,
+            status: HitStatus::Xlu,
+        }
+    }
+}
+
+impl Action for HitNo {
+    const NAME: &'static str = "Hit.set_no";
+    const ACMD_NAME: &'static str = "HIT_NO";
+
+    fn metadata() -> ActionMetadata {
+        ActionMetadata::default()
+            .category(Category::Game)
+            .defaults(&Self::default())
+    }
+
+    fn to_rust(&self) -> Option<String> {
+        Some(format!(
+            "macros::HIT_NO(agent, {}, *{});",
+            self.part,
+            self.status.lua_const()
+        ))
+    }
+
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        context.hit_no(self.part, self.status.into());
+    }
+}
+
+decl_action!(
+    /// Puts every hurtbox changed with `Hit.set_node` or `Hit.set_no` back to normal
+    #[derive(Default)]
+    pub struct HitResetAll;
+);
+
+impl Action for HitResetAll {
+    const NAME: &'static str = "Hit.reset_all";
+    const ACMD_NAME: &'static str = "HIT_RESET_ALL";
+
+    fn metadata() -> ActionMetadata {
+        ActionMetadata::default().category(Category::Game)
+    }
+
+    fn to_rust(&self) -> Option<String> {
+        Some("macros::HIT_RESET_ALL(agent);".to_string())
+    }
+
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        context.hit_reset_all();
+    }
+}
+
+decl_action!(
+    /// Turns the pushing between the agent and other fighters on or off
+    #[derive(Copy)]
+    pub struct SetBodyCollision(pub bool);
+);
+
+impl Default for SetBodyCollision {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+impl Action for SetBodyCollision {
+    const NAME: &'static str = "Jostle.set_status";
+    const ACMD_NAME: &'static str = "JostleModule.set_status";
+
+    fn metadata() -> ActionMetadata {
+        ActionMetadata::default().category(Category::Game)
+    }
+
+    fn to_rust(&self) -> Option<String> {
+        Some(format!(
+            "JostleModule::set_status(agent.module_accessor, {});",
+            self.0
+        ))
+    }
+
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        context.set_body_collision(self.0);
+    }
+}
+
+decl_action!(
+    /// Turns one of the agent's shield or reflector boxes on or off
+    pub struct Shield {
+        pub enabled: bool,
+        /// Such as `COLLISION_KIND_SHIELD` or `COLLISION_KIND_REFLECTOR`
+        pub kind: LuaConst,
+        /// The box to turn on or off, such as `FIGHTER_FOX_REFLECTOR_KIND_REFLECTOR`
+        pub id: LuaConst,
+        /// Such as `FIGHTER_REFLECTOR_GROUP_EXTEND`
+        pub group: LuaConst,
+    }
+);
+
+impl Default for Shield {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            kind: LuaConst::new("COLLISION_KIND_SHIELD"),
+            // Every fighter names the ids and groups of its boxes differently, so these are left
+            // for the script to fill in and aren't advertised as defaults
+            id: LuaConst::default(),
+            group: LuaConst::default(),
+        }
+    }
+}
+
+impl Action for Shield {
+    const NAME: &'static str = "Shield.set";
+    const ACMD_NAME: &'static str = "shield";
+
+    fn metadata() -> ActionMetadata {
+        let Self { enabled, kind, .. } = Self::default();
+        ActionMetadata::default()
+            .category(Category::Game)
+            .defaults(&serde_json::json!({ "enabled": enabled, "kind": kind }))
+    }
+
+    fn to_rust(&self) -> Option<String> {
+        let command = if self.enabled {
+            "MA_MSC_CMD_SHIELD_ON"
+        } else {
+            "MA_MSC_CMD_SHIELD_OFF"
+        };
+
+        Some(format!(
+            "shield!(agent, *{command}, {}, {}, {});",
+            self.kind.to_rust(),
+            self.id.to_rust(),
+            self.group.to_rust()
+        ))
+    }
+
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        context.shield(
+            self.enabled,
+            self.kind.value,
+            self.id.value,
+            self.group.value,
+        );
+    }
+}
@@ -1,10 +1,24 @@
 use std::fmt::Display;
 
-use acmd_engine::action::Action;
+use acmd_engine::{
+    action::{Action, RegistrationError},
+    codegen,
+    context::ExecutionContext,
+    timeline::Wait,
+};
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
+pub use acmd_engine;
+pub use schemars;
 pub use serde;
 
 pub mod attack;
+pub mod control;
+pub mod effect;
+pub mod expression;
+pub mod hit;
 pub mod lua_const;
+pub mod motion;
+pub mod sound;
 pub mod work;
 
 #[cfg(feature = "bevy_reflect")]
@@ -18,7 +32,13 @@ macro_rules! decl_action {
     ) => {
         #[cfg_attr(feature = "bevy_reflect", derive(::bevy_reflect::Reflect))]
         #[cfg_attr(feature = "bevy_reflect", reflect(Default))]
-        #[derive($crate::serde::Deserialize, $crate::serde::Serialize, Debug, Clone)]
+        #[derive(
+            $crate::serde::Deserialize,
+            $crate::serde::Serialize,
+            $crate::schemars::JsonSchema,
+            Debug,
+            Clone
+        )]
         $(#[$outer])*
         pub struct $Name $($t)*
     };
@@ -28,7 +48,13 @@ macro_rules! decl_action {
     ) => {
         #[cfg_attr(feature = "bevy_reflect", derive(::bevy_reflect::Reflect))]
         #[cfg_attr(feature = "bevy_reflect", reflect(Default))]
-        #[derive($crate::serde::Deserialize, $crate::serde::Serialize, Debug, Clone)]
+        #[derive(
+            $crate::serde::Deserialize,
+            $crate::serde::Serialize,
+            $crate::schemars::JsonSchema,
+            Debug,
+            Clone
+        )]
         $(#[$outer])*
         pub enum $Name $($t)*
     }
@@ -44,63 +70,95 @@ macro_rules! impl_action {
         impl Action for $id {
             const NAME: &'static str = $name;
 
-            unsafe fn execute(&self, fighter: &mut smash::lua2cpp::L2CAgentBase) {
-                #[cfg(feature = "bevy_reflect")]
-                {
-                    unimplemented!()
-                }
-                #[cfg(not(feature = "bevy_reflect"))]
-                {
-                    $($t)*
-                }
+            unsafe fn execute(
+                &self,
+                context: &mut dyn $crate::acmd_engine::context::ExecutionContext,
+            ) {
+                $($t)*
             }
         }
     }
 }
 
 decl_action! {
+    /// Waits until the script reaches the given frame
     #[derive(Copy, Default)]
     pub struct WaitUntil(f32);
 }
 
 impl Action for WaitUntil {
     const NAME: &'static str = "WaitUntil";
+    const ACMD_NAME: &'static str = "frame";
 
-    #[cfg(feature = "bevy_reflect")]
-    unsafe fn execute(&self, context: &mut smash::lua2cpp::L2CAgentBase) {
-        unimplemented!()
+    fn to_rust(&self) -> Option<String> {
+        Some(format!(
+            "frame(agent.lua_state_agent, {});",
+            codegen::float(self.0)
+        ))
     }
 
-    #[cfg(not(feature = "bevy_reflect"))]
-    unsafe fn execute(&self, context: &mut smash::lua2cpp::L2CAgentBase) {
-        smash::app::sv_animcmd::frame(context.lua_state_agent, self.0);
+    fn wait(&self) -> Option<Wait> {
+        Some(Wait::Until(self.0))
+    }
+
+    fn from_wait(wait: Wait) -> Option<Self> {
+        match wait {
+            Wait::Until(frame) => Some(Self(frame)),
+            _ => None,
+        }
+    }
+
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        context.frame(self.0);
     }
 }
 
 decl_action! {
+    /// Waits for the given number of frames
     #[derive(Copy, Default)]
     pub struct WaitFor(f32);
 }
 
 impl Action for WaitFor {
     const NAME: &'static str = "WaitFor";
+    const ACMD_NAME: &'static str = "wait";
+
+    fn to_rust(&self) -> Option<String> {
+        Some(format!(
+            "wait(agent.lua_state_agent, {});",
+            codegen::float(self.0)
+        ))
+    }
+
+    fn wait(&self) -> Option<Wait> {
+        Some(Wait::For(self.0))
+    }
 
-    #[cfg(feature = "bevy_reflect")]
-    unsafe fn execute(&self, context: &mut smash::lua2cpp::L2CAgentBase) {
-        unimplemented!()
+    fn from_wait(wait: Wait) -> Option<Self> {
+        match wait {
+            Wait::For(frames) => Some(Self(frames)),
+            _ => None,
+        }
     }
 
-    #[cfg(not(feature = "bevy_reflect"))]
-    unsafe fn execute(&self, context: &mut smash::lua2cpp::L2CAgentBase) {
-        smash::app::sv_animcmd::wait(context.lua_state_agent, self.0);
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        context.wait(self.0);
     }
 }
 
-pub fn register_defaults() {
+pub fn register_defaults() -> Result<(), RegistrationError> {
     macro_rules! register {
         ($($action:path),*) => {
             $(
-                smashline::api::register_action::<$action>();
+                smashline::api::register_action::<$action>()?;
+            )*
+        }
+    }
+
+    macro_rules! register_control {
+        ($($action:path),*) => {
+            $(
+                smashline::api::register_control_action::<$action>()?;
             )*
         }
     }
@@ -115,13 +173,51 @@ pub fn register_defaults() {
         work::SetFlag,
         attack::Attack,
         attack::AttackClear,
-        attack::AttackClearAll
+        attack::AttackClearAll,
+        attack::Catch,
+        attack::CatchClearAll,
+        attack::AttackAbs,
+        attack::AttackHitAbs,
+        hit::SetHitStatusAll,
+        hit::SetHitWhole,
+        hit::HitNode,
+        hit::HitNo,
+        hit::HitResetAll,
+        hit::SetBodyCollision,
+        hit::Shield,
+        motion::SetMotionRate,
+        motion::DesiredRate,
+        motion::MotionRateRange,
+        effect::Effect,
+        effect::EffectFollow,
+        effect::LandingEffect,
+        effect::LastEffectSetColor,
+        effect::LastEffectSetRate,
+        effect::EffectOffKind,
+        sound::PlaySe,
+        sound::PlaySeRemain,
+        sound::PlaySequence,
+        sound::PlayStatus,
+        sound::StopSe,
+        expression::Rumble,
+        expression::Slope,
+        expression::SetVisibleWhole,
+        expression::SetVisibility,
+        expression::SetHaveItemVisibility
+    }
+
+    register_control! {
+        control::If,
+        control::Repeat,
+        control::While
     }
+
+    Ok(())
 }
 
 #[cfg(not(feature = "bevy_reflect"))]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
-pub struct SerdeHash40(pub hash40::Hash40);
+pub struct SerdeHash40(#[serde(with = "acmd_engine::labels::hash")] pub hash40::Hash40);
 
 #[cfg(feature = "bevy_reflect")]
 #[derive(
@@ -136,7 +232,17 @@ pub struct SerdeHash40(pub hash40::Hash40);
     Default,
 )]
 #[reflect_value(Default)]
-pub struct SerdeHash40(pub hash40::Hash40);
+pub struct SerdeHash40(#[serde(with = "acmd_engine::labels::hash")] pub hash40::Hash40);
+
+impl JsonSchema for SerdeHash40 {
+    fn schema_name() -> String {
+        "Hash40".to_string()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        String::json_schema(generator)
+    }
+}
 
 impl SerdeHash40 {
     pub const fn new(string: &str) -> Self {
@@ -146,6 +252,6 @@ impl SerdeHash40 {
 
 impl Display for SerdeHash40 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        Display::fmt(&self.0, f)
+        f.write_str(&acmd_engine::labels::to_string(self.0))
     }
 }
@@ -1,3 +1,4 @@
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
 use serde::{Deserialize, Serialize};
 
 #[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
@@ -8,6 +9,22 @@ pub struct LuaConst {
     pub value: i32,
 }
 
+impl LuaConst {
+    /// The constant named `name`, looked up the same way as when it is deserialized. It keeps the
+    /// value `0` when it can't be looked up, such as in a tool that hasn't set it, since defaults
+    /// can't fail.
+    pub fn new(name: impl Into<String>) -> Self {
+        let name = name.into();
+        let value = acmd_engine::context::lua_const(&name).unwrap_or(0);
+        Self { name, value }
+    }
+
+    /// The constant as it is written in Rust, dereferencing the `lua_const` of the same name
+    pub fn to_rust(&self) -> String {
+        format!("*{}", self.name)
+    }
+}
+
 impl Serialize for LuaConst {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -17,25 +34,27 @@ impl Serialize for LuaConst {
     }
 }
 
-#[cfg_attr(feature = "bevy_reflect", allow(unused_mut))]
+impl JsonSchema for LuaConst {
+    fn schema_name() -> String {
+        "LuaConst".to_string()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        String::json_schema(generator)
+    }
+}
+
 impl<'de> Deserialize<'de> for LuaConst {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
         let name = String::deserialize(deserializer)?;
-
-        let mut value = 0i32;
-        #[cfg(not(feature = "bevy_reflect"))]
-        {
-            if unsafe {
-                !smash::lib::lua_bind_get_value(lua_bind_hash::lua_bind_hash_str(&name), &mut value)
-            } {
-                return Err(<D::Error as serde::de::Error>::custom(format!(
-                    "failed to get lua bind value for {name}"
-                )));
-            }
-        }
+        let Some(value) = acmd_engine::context::lua_const(&name) else {
+            return Err(<D::Error as serde::de::Error>::custom(format!(
+                "failed to get lua bind value for {name}"
+            )));
+        };
 
         Ok(Self { name, value })
     }
@@ -0,0 +1,208 @@
+use acmd_engine::{
+    action::Action, asset::Category, codegen, context::ExecutionContext, metadata::ActionMetadata,
+    timeline::MotionRate,
+};
+use serde::Deserialize;
+
+#[cfg(feature = "bevy_reflect")]
+use bevy_reflect::prelude::*;
+
+use crate::decl_action;
+
+/// Rates and frame counts end up as divisors, so anything that isn't above zero is rejected
+fn positive(name: &str, value: f32) -> Result<f32, String> {
+    if value.is_finite() && value > 0.0 {
+        Ok(value)
+    } else {
+        Err(format!("'{name}' must be greater than 0, found {value}"))
+    }
+}
+
+decl_action!(
+    /// Sets the speed the animation plays at, which the frames of the script follow. A rate of
+    /// `0.5` makes every frame take two.
+    #[derive(Copy)]
+    #[serde(try_from = "f32")]
+    pub struct SetMotionRate(f32);
+);
+
+decl_action!(
+    /// Sets the motion rate so that the next `frames` frames of the script take `desired` frames
+    #[derive(Copy)]
+    #[serde(try_from = "DesiredRateFields")]
+    pub struct DesiredRate {
+        frames: f32,
+        desired: f32,
+    }
+);
+
+decl_action!(
+    /// Plays the frames of the script from `start` to `end` over `desired` frames, then goes back
+    /// to the previous motion rate
+    #[derive(Copy)]
+    #[serde(try_from = "MotionRateRangeFields")]
+    pub struct MotionRateRange {
+        start: f32,
+        end: f32,
+        desired: f32,
+    }
+);
+
+#[derive(Deserialize)]
+struct DesiredRateFields {
+    frames: f32,
+    desired: f32,
+}
+
+#[derive(Deserialize)]
+struct MotionRateRangeFields {
+    start: f32,
+    end: f32,
+    desired: f32,
+}
+
+impl TryFrom<f32> for SetMotionRate {
+    type Error = String;
+
+    fn try_from(rate: f32) -> Result<Self, Self::Error> {
+        positive("rate", rate).map(Self)
+    }
+}
+
+impl TryFrom<DesiredRateFields> for DesiredRate {
+    type Error = String;
+
+    fn try_from(fields: DesiredRateFields) -> Result<Self, Self::Error> {
+        Ok(Self {
+            frames: positive("frames", fields.frames)?,
+            desired: positive("desired", fields.desired)?,
+        })
+    }
+}
+
+impl TryFrom<MotionRateRangeFields> for MotionRateRange {
+    type Error = String;
+
+    fn try_from(fields: MotionRateRangeFields) -> Result<Self, Self::Error> {
+        if !(fields.start >= 0.0 && fields.end > fields.start) {
+            return Err(format!(
+                "the range {}..{} must start at 0 or later and end after it starts",
+                fields.start, fields.end
+            ));
+        }
+
+        Ok(Self {
+            start: fields.start,
+            end: fields.end,
+            desired: positive("desired", fields.desired)?,
+        })
+    }
+}
+
+impl Default for SetMotionRate {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+impl Default for DesiredRate {
+    fn default() -> Self {
+        Self {
+            frames: 1.0,
+            desired: 1.0,
+        }
+    }
+}
+
+impl Default for MotionRateRange {
+    fn default() -> Self {
+        Self {
+            start: 0.0,
+            end: 1.0,
+            desired: 1.0,
+        }
+    }
+}
+
+impl Action for SetMotionRate {
+    const NAME: &'static str = "Motion.set_rate";
+    const ACMD_NAME: &'static str = "FT_MOTION_RATE";
+
+    fn metadata() -> ActionMetadata {
+        ActionMetadata::default().category(Category::Game)
+    }
+
+    fn to_rust(&self) -> Option<String> {
+        Some(format!(
+            "macros::FT_MOTION_RATE(agent, {});",
+            codegen::float(self.0)
+        ))
+    }
+
+    fn motion_rate(&self) -> Option<MotionRate> {
+        Some(MotionRate::Set(self.0))
+    }
+
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        context.ft_motion_rate(self.0);
+    }
+}
+
+impl Action for DesiredRate {
+    const NAME: &'static str = "Motion.desired_rate";
+    const ACMD_NAME: &'static str = "FT_DESIRED_RATE";
+
+    fn metadata() -> ActionMetadata {
+        ActionMetadata::default()
+            .category(Category::Game)
+            .defaults(&Self::default())
+    }
+
+    fn to_rust(&self) -> Option<String> {
+        Some(format!(
+            "macros::FT_DESIRED_RATE(agent, {}, {});",
+            codegen::float(self.frames),
+            codegen::float(self.desired)
+        ))
+    }
+
+    fn motion_rate(&self) -> Option<MotionRate> {
+        Some(MotionRate::Set(self.frames / self.desired))
+    }
+
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        context.ft_desired_rate(self.frames, self.desired);
+    }
+}
+
+impl Action for MotionRateRange {
+    const NAME: &'static str = "Motion.rate_range";
+    const ACMD_NAME: &'static str = "FT_MOTION_RATE_RANGE";
+
+    fn metadata() -> ActionMetadata {
+        ActionMetadata::default()
+            .category(Category::Game)
+            .defaults(&Self::default())
+    }
+
+    fn to_rust(&self) -> Option<String> {
+        Some(format!(
+            "macros::FT_MOTION_RATE_RANGE(agent, {}, {}, {});",
+            codegen::float(self.start),
+            codegen::float(self.end),
+            codegen::float(self.desired)
+        ))
+    }
+
+    fn motion_rate(&self) -> Option<MotionRate> {
+        Some(MotionRate::Range {
+            start: self.start,
+            end: self.end,
+            rate: (self.end - self.start) / self.desired,
+        })
+    }
+
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        context.ft_motion_rate_range(self.start, self.end, self.desired);
+    }
+}
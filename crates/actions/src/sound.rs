@@ -0,0 +1,74 @@
+use acmd_engine::{
+    action::Action, asset::Category, codegen, context::ExecutionContext, metadata::ActionMetadata,
+};
+
+#[cfg(feature = "bevy_reflect")]
+use bevy_reflect::prelude::*;
+
+use crate::{decl_action, SerdeHash40};
+
+decl_action!(
+    /// Plays a sound effect, which stops when the animation changes
+    #[derive(Copy, Default)]
+    pub struct PlaySe(pub SerdeHash40);
+);
+
+decl_action!(
+    /// Plays a sound effect that keeps playing after the animation changes
+    #[derive(Copy, Default)]
+    pub struct PlaySeRemain(pub SerdeHash40);
+);
+
+decl_action!(
+    /// Plays one of the sounds of a sequence, such as the voice clips of an attack
+    #[derive(Copy, Default)]
+    pub struct PlaySequence(pub SerdeHash40);
+);
+
+decl_action!(
+    /// Plays a sound effect that stops when the agent's status changes
+    #[derive(Copy, Default)]
+    pub struct PlayStatus(pub SerdeHash40);
+);
+
+decl_action!(
+    /// Stops a sound effect that is playing
+    #[derive(Copy, Default)]
+    pub struct StopSe(pub SerdeHash40);
+);
+
+/// Implements [`Action`] for a sound action, which passes its sound to the `ACMD_NAME` macro and
+/// to the context method of the same name
+macro_rules! impl_sound_action {
+    ($($Name:ident($name:literal, $acmd_name:literal, $method:ident);)*) => {
+        $(
+            impl Action for $Name {
+                const NAME: &'static str = $name;
+                const ACMD_NAME: &'static str = $acmd_name;
+
+                fn metadata() -> ActionMetadata {
+                    ActionMetadata::default().category(Category::Sound)
+                }
+
+                fn to_rust(&self) -> Option<String> {
+                    Some(format!(
+                        concat!("macros::", $acmd_name, "(agent, {});"),
+                        codegen::hash(self.0 .0)
+                    ))
+                }
+
+                unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+                    context.$method(self.0 .0);
+                }
+            }
+        )*
+    };
+}
+
+impl_sound_action! {
+    PlaySe("Sound.play", "PLAY_SE", play_se);
+    PlaySeRemain("Sound.play_remain", "PLAY_SE_REMAIN", play_se_remain);
+    PlaySequence("Sound.play_sequence", "PLAY_SEQUENCE", play_sequence);
+    PlayStatus("Sound.play_status", "PLAY_STATUS", play_status);
+    StopSe("Sound.stop", "STOP_SE", stop_se);
+}
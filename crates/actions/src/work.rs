@@ -1,5 +1,4 @@
-use acmd_engine::action::Action;
-use smash::app::lua_bind::WorkModule;
+use acmd_engine::{action::Action, context::ExecutionContext, expr::Expr};
 
 #[cfg(feature = "bevy_reflect")]
 use bevy_reflect::prelude::*;
@@ -8,14 +7,17 @@ use crate::decl_action;
 use crate::lua_const::LuaConst;
 
 decl_action!(
+    /// Turns on a flag in the agent's WorkModule
     #[derive(Default)]
     pub struct OnFlag(LuaConst);
 );
 decl_action!(
+    /// Turns off a flag in the agent's WorkModule
     #[derive(Default)]
     pub struct OffFlag(LuaConst);
 );
 decl_action!(
+    /// Sets an int in the agent's WorkModule
     #[derive(Default)]
     pub struct SetInt {
         variable: LuaConst,
@@ -23,13 +25,16 @@ decl_action!(
     }
 );
 decl_action!(
+    /// Sets a float in the agent's WorkModule
     #[derive(Default)]
     pub struct SetFloat {
         variable: LuaConst,
-        value: f32,
+        /// Written as a number or an expression
+        value: Expr,
     }
 );
 decl_action!(
+    /// Turns a flag in the agent's WorkModule on or off
     #[derive(Default)]
     pub struct SetFlag {
         variable: LuaConst,
@@ -39,70 +44,84 @@ decl_action!(
 
 impl Action for OnFlag {
     const NAME: &'static str = "Work.on_flag";
+    const ACMD_NAME: &'static str = "WorkModule.on_flag";
 
-    #[cfg(feature = "bevy_reflect")]
-    unsafe fn execute(&self, context: &mut smash::lua2cpp::L2CAgentBase) {
-        unimplemented!()
+    fn to_rust(&self) -> Option<String> {
+        Some(format!(
+            "WorkModule::on_flag(agent.module_accessor, {});",
+            self.0.to_rust()
+        ))
     }
 
-    #[cfg(not(feature = "bevy_reflect"))]
-    unsafe fn execute(&self, context: &mut smash::lua2cpp::L2CAgentBase) {
-        WorkModule::on_flag(context.module_accessor, self.0.value);
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        context.on_flag(self.0.value);
     }
 }
 
 impl Action for OffFlag {
     const NAME: &'static str = "Work.off_flag";
+    const ACMD_NAME: &'static str = "WorkModule.off_flag";
 
-    #[cfg(feature = "bevy_reflect")]
-    unsafe fn execute(&self, context: &mut smash::lua2cpp::L2CAgentBase) {
-        unimplemented!()
+    fn to_rust(&self) -> Option<String> {
+        Some(format!(
+            "WorkModule::off_flag(agent.module_accessor, {});",
+            self.0.to_rust()
+        ))
     }
 
-    #[cfg(not(feature = "bevy_reflect"))]
-    unsafe fn execute(&self, context: &mut smash::lua2cpp::L2CAgentBase) {
-        WorkModule::off_flag(context.module_accessor, self.0.value);
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        context.off_flag(self.0.value);
     }
 }
 
 impl Action for SetInt {
     const NAME: &'static str = "Work.set_int";
-
-    #[cfg(feature = "bevy_reflect")]
-    unsafe fn execute(&self, context: &mut smash::lua2cpp::L2CAgentBase) {
-        unimplemented!()
+    const ACMD_NAME: &'static str = "WorkModule.set_int";
+
+    fn to_rust(&self) -> Option<String> {
+        Some(format!(
+            "WorkModule::set_int(agent.module_accessor, {}, {});",
+            self.value,
+            self.variable.to_rust()
+        ))
     }
 
-    #[cfg(not(feature = "bevy_reflect"))]
-    unsafe fn execute(&self, context: &mut smash::lua2cpp::L2CAgentBase) {
-        WorkModule::set_int(context.module_accessor, self.value, self.variable.value);
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        context.set_int(self.variable.value, self.value);
     }
 }
 
 impl Action for SetFloat {
     const NAME: &'static str = "Work.set_float";
-
-    #[cfg(feature = "bevy_reflect")]
-    unsafe fn execute(&self, context: &mut smash::lua2cpp::L2CAgentBase) {
-        unimplemented!()
+    const ACMD_NAME: &'static str = "WorkModule.set_float";
+
+    fn to_rust(&self) -> Option<String> {
+        Some(format!(
+            "WorkModule::set_float(agent.module_accessor, {}, {});",
+            self.value.to_rust(),
+            self.variable.to_rust()
+        ))
     }
 
-    #[cfg(not(feature = "bevy_reflect"))]
-    unsafe fn execute(&self, context: &mut smash::lua2cpp::L2CAgentBase) {
-        WorkModule::set_float(context.module_accessor, self.value, self.variable.value);
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        let value = self.value.evaluate(context);
+        context.set_float(self.variable.value, value);
     }
 }
 
 impl Action for SetFlag {
     const NAME: &'static str = "Work.set_flag";
-
-    #[cfg(feature = "bevy_reflect")]
-    unsafe fn execute(&self, context: &mut smash::lua2cpp::L2CAgentBase) {
-        unimplemented!()
+    const ACMD_NAME: &'static str = "WorkModule.set_flag";
+
+    fn to_rust(&self) -> Option<String> {
+        Some(format!(
+            "WorkModule::set_flag(agent.module_accessor, {}, {});",
+            self.value,
+            self.variable.to_rust()
+        ))
     }
 
-    #[cfg(not(feature = "bevy_reflect"))]
-    unsafe fn execute(&self, context: &mut smash::lua2cpp::L2CAgentBase) {
-        WorkModule::set_flag(context.module_accessor, self.value, self.variable.value);
+    unsafe fn execute(&self, context: &mut dyn ExecutionContext) {
+        context.set_flag(self.variable.value, self.value);
     }
 }
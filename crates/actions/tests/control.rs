@@ -0,0 +1,58 @@
+use acmd_engine::{action::ActionRegistry, call::Call};
+use serde_json::{json, Value};
+
+fn registry() -> ActionRegistry {
+    let registry = ActionRegistry::new();
+    registry
+        .register::<actions::WaitUntil>()
+        .unwrap()
+        .register::<actions::WaitFor>()
+        .unwrap()
+        .register_control::<actions::control::If>()
+        .unwrap()
+        .register_control::<actions::control::Repeat>()
+        .unwrap()
+        .register_control::<actions::control::While>()
+        .unwrap()
+        .register_control::<Call>()
+        .unwrap();
+    registry
+}
+
+fn while_loop(actions: Value) -> Value {
+    json!({ "While": { "condition": "IsExcute", "actions": actions } })
+}
+
+#[test]
+fn while_waits() {
+    let registry = registry();
+    let body = json!([{ "WaitFor": 1.0 }]);
+    assert!(registry.as_action(while_loop(body)).is_ok());
+
+    let body = json!([{ "If": {
+        "condition": "IsExcute",
+        "then": [{ "WaitFor": 1.0 }],
+        "else": [{ "Repeat": { "count": 2, "actions": [{ "WaitFor": 2.0 }] } }]
+    } }]);
+    assert!(registry.as_action(while_loop(body)).is_ok());
+}
+
+#[test]
+fn while_never_waits() {
+    let registry = registry();
+    let bodies = [
+        json!([]),
+        json!([{ "WaitFor": 0.0 }]),
+        json!([{ "WaitUntil": 10.0 }]),
+        json!([{ "If": { "condition": "IsExcute", "then": [{ "WaitFor": 1.0 }] } }]),
+        json!([{ "Repeat": { "count": 0, "actions": [{ "WaitFor": 1.0 }] } }]),
+        json!([{ "Call": { "File": "wait.acmd" } }]),
+    ];
+
+    for body in bodies {
+        assert!(
+            registry.as_action(while_loop(body.clone())).is_err(),
+            "{body} was accepted"
+        );
+    }
+}
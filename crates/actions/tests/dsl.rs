@@ -0,0 +1,73 @@
+use acmd_engine::{action::ActionRegistry, context::set_lua_const, SmashlineScript};
+
+fn registry() -> ActionRegistry {
+    let registry = ActionRegistry::new();
+    registry
+        .register::<actions::WaitUntil>()
+        .unwrap()
+        .register::<actions::WaitFor>()
+        .unwrap()
+        .register::<actions::work::OnFlag>()
+        .unwrap()
+        .register::<actions::work::SetFloat>()
+        .unwrap()
+        .register::<actions::attack::AttackClearAll>()
+        .unwrap()
+        .register_control::<actions::control::If>()
+        .unwrap();
+    registry
+}
+
+/// A script as [`acmd_engine::dsl::print`] writes it, with its hashes in hex since no labels are
+/// loaded
+fn source() -> String {
+    set_lua_const("TEST_DSL_FLAG", 1);
+    set_lua_const("TEST_DSL_FLOAT", 2);
+
+    let agent = hash40::hash40("mario").0;
+    let replace = hash40::hash40("game_attack11").0;
+    format!(
+        r#"agent = "{agent:#012x}"
+category = Game
+replace = "{replace:#012x}"
+
+frame(5.0)
+WorkModule.on_flag(TEST_DSL_FLAG)
+WorkModule.set_float(value = "1.5 * 2", variable = TEST_DSL_FLOAT)
+If(condition = IsExcute) then
+    AttackModule.clear_all()
+else
+    wait(1.0)
+end
+"#
+    )
+}
+
+#[test]
+fn text() {
+    let registry = registry();
+    let source = source();
+
+    let script = SmashlineScript::from_text(&registry, &source).unwrap();
+    assert_eq!(script.actions.len(), 4);
+    assert_eq!(script.to_text(&registry).unwrap(), source);
+}
+
+#[test]
+fn json() {
+    let registry = registry();
+    let source = source();
+
+    let script = SmashlineScript::from_text(&registry, &source).unwrap();
+    let json = script.to_json(&registry).unwrap();
+    let script = SmashlineScript::from_json(&registry, &json).unwrap();
+    assert_eq!(script.to_text(&registry).unwrap(), source);
+    assert_eq!(script.to_json(&registry).unwrap(), json);
+
+    let value = script.to_json_value(&registry).unwrap();
+    assert_eq!(value["actions"][0], serde_json::json!({ "WaitUntil": 5.0 }));
+    assert_eq!(
+        value["actions"][1],
+        serde_json::json!({ "Work.on_flag": "TEST_DSL_FLAG" })
+    );
+}
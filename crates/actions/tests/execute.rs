@@ -0,0 +1,113 @@
+use acmd_engine::{
+    action::ActionRegistry,
+    context::{set_lua_const, RecordedCall, RecordingContext},
+};
+use serde_json::{json, Value};
+
+fn registry() -> ActionRegistry {
+    let registry = ActionRegistry::new();
+    registry
+        .register::<actions::WaitUntil>()
+        .unwrap()
+        .register::<actions::work::OnFlag>()
+        .unwrap()
+        .register::<actions::work::SetFloat>()
+        .unwrap()
+        .register::<actions::attack::Attack>()
+        .unwrap()
+        .register::<actions::attack::Catch>()
+        .unwrap();
+    registry
+}
+
+fn run(context: &mut RecordingContext, action: Value) {
+    let registry = registry();
+    let action = registry.as_action(action).unwrap();
+    registry.execute(&action, context).unwrap();
+}
+
+#[test]
+fn wait_until() {
+    let mut context = RecordingContext::new();
+    run(&mut context, json!({ "WaitUntil": 5.0 }));
+
+    assert_eq!(context.calls, [RecordedCall::Frame(5.0)]);
+    assert_eq!(context.frame, 5.0);
+}
+
+#[test]
+fn work() {
+    set_lua_const("TEST_WORK_FLAG", 1);
+    set_lua_const("TEST_WORK_FLOAT", 2);
+    set_lua_const("TEST_WORK_FLOAT_CHARGE", 3);
+
+    let mut context = RecordingContext::new();
+    context.floats.insert(3, 4.0);
+    run(&mut context, json!({ "Work.on_flag": "TEST_WORK_FLAG" }));
+    run(
+        &mut context,
+        json!({
+            "Work.set_float": {
+                "variable": "TEST_WORK_FLOAT",
+                "value": "work_float(TEST_WORK_FLOAT_CHARGE) * 2 + 1"
+            }
+        }),
+    );
+
+    assert_eq!(
+        context.calls,
+        [
+            RecordedCall::OnFlag(1),
+            RecordedCall::SetFloat {
+                variable: 2,
+                value: 9.0
+            }
+        ]
+    );
+    assert_eq!(context.flags.get(&1), Some(&true));
+}
+
+#[test]
+fn unknown_constant() {
+    let error = registry().as_action(json!({ "Work.on_flag": "TEST_MISSPELLED_FLAG" }));
+    assert!(error.is_err());
+}
+
+#[test]
+fn attack() {
+    let mut attack = serde_json::to_value(actions::attack::Attack::default()).unwrap();
+    attack["id"] = json!(1);
+    attack["damage"] = json!("10 * 1.5");
+    attack["shape"] = json!({ "Capsule": { "p1": [0.0, 2.0, 3.0], "p2": [0.0, 2.0, 9.0] } });
+
+    let mut context = RecordingContext::new();
+    run(&mut context, json!({ "Attack.set": attack }));
+
+    let [RecordedCall::Attack(params)] = context.calls.as_slice() else {
+        panic!("expected a single attack, got {:?}", context.calls);
+    };
+    assert_eq!(params.id, 1);
+    assert_eq!(params.bone, hash40::hash40("top"));
+    assert_eq!(params.damage, 15.0);
+    assert_eq!(params.size, 5.0);
+    assert_eq!(params.offset, [0.0, 2.0, 3.0]);
+    assert_eq!(params.offset2, Some([0.0, 2.0, 9.0]));
+}
+
+#[test]
+fn catch() {
+    set_lua_const("FIGHTER_STATUS_KIND_CAPTURE_PULLED", 4);
+
+    let mut catch = serde_json::to_value(actions::attack::Catch::default()).unwrap();
+    catch["radius"] = json!("2 + 1.5");
+
+    let mut context = RecordingContext::new();
+    run(&mut context, json!({ "Catch.set": catch }));
+
+    let [RecordedCall::Catch(params)] = context.calls.as_slice() else {
+        panic!("expected a single grab box, got {:?}", context.calls);
+    };
+    assert_eq!(params.size, 3.5);
+    assert_eq!(params.status, 4);
+    assert_eq!(actions::attack::Catch::default().status.value, 4);
+}
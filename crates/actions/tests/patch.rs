@@ -0,0 +1,152 @@
+use acmd_engine::{
+    action::{ActionError, ActionRegistry},
+    context::set_lua_const,
+    template::Templates,
+    ScriptFile, SmashlineScript,
+};
+use serde_json::{json, Value};
+
+fn registry() -> ActionRegistry {
+    let registry = ActionRegistry::new();
+    registry
+        .register::<actions::WaitUntil>()
+        .unwrap()
+        .register::<actions::WaitFor>()
+        .unwrap()
+        .register::<actions::work::OnFlag>()
+        .unwrap()
+        .register::<actions::work::SetFloat>()
+        .unwrap();
+    registry
+}
+
+/// Applies the operations to a script, returning the resulting actions as JSON
+fn patch(operations: Value) -> Result<Vec<Value>, ActionError> {
+    set_lua_const("TEST_PATCH_FLAG", 1);
+    set_lua_const("TEST_PATCH_FLOAT", 2);
+
+    let registry = registry();
+    let templates = Templates::default();
+    let script = json!({
+        "version": "0.2.0",
+        "agent": "mario",
+        "category": "Game",
+        "replace": "game_attack11",
+        "actions": [
+            { "WaitUntil": 5.0 },
+            { "Work.on_flag": "TEST_PATCH_FLAG" },
+            { "WaitUntil": 10.0 },
+            { "Work.set_float": { "variable": "TEST_PATCH_FLOAT", "value": 1.0 } },
+            { "WaitFor": 2.0 }
+        ]
+    });
+    let script = SmashlineScript::from_json(&registry, script.to_string())?;
+
+    let patch = json!({
+        "version": "0.2.0",
+        "agent": "mario",
+        "category": "Game",
+        "patch": "game_attack11",
+        "operations": operations
+    });
+    let ScriptFile::Patch(patch) = ScriptFile::from_json(&registry, &templates, patch.to_string())?
+    else {
+        panic!("expected a patch");
+    };
+    assert!(patch.targets(&script));
+
+    let script = patch.apply(&registry, &templates, &script)?;
+    let value = script.to_json_value(&registry)?;
+    Ok(value["actions"].as_array().unwrap().clone())
+}
+
+#[test]
+fn index() {
+    let actions = patch(json!([
+        { "Remove": { "target": 4 } },
+        { "Insert": { "at": "start", "actions": [{ "WaitUntil": 1.0 }] } },
+        { "Replace": { "target": 2, "actions": [{ "WaitFor": 1.0 }, { "WaitFor": 2.0 }] } },
+        { "Insert": { "at": "end", "actions": [{ "WaitUntil": 20.0 }] } }
+    ]))
+    .unwrap();
+
+    assert_eq!(
+        actions,
+        [
+            json!({ "WaitUntil": 1.0 }),
+            json!({ "WaitUntil": 5.0 }),
+            json!({ "WaitFor": 1.0 }),
+            json!({ "WaitFor": 2.0 }),
+            json!({ "WaitUntil": 10.0 }),
+            json!({ "Work.set_float": { "variable": "TEST_PATCH_FLOAT", "value": 1.0 } }),
+            json!({ "WaitUntil": 20.0 }),
+        ]
+    );
+}
+
+#[test]
+fn matches() {
+    let actions = patch(json!([
+        // Matched by the ACMD name as well as the registered one
+        { "Edit": {
+            "target": { "action": "WorkModule.set_float", "fields": { "variable": "TEST_PATCH_FLOAT" } },
+            "set": { "value": 3.0 }
+        } },
+        { "Insert": {
+            "at": { "after": { "action": "frame", "fields": 10 } },
+            "actions": [{ "Work.on_flag": "TEST_PATCH_FLAG" }]
+        } },
+        { "Remove": { "target": { "action": "WaitUntil" } } }
+    ]))
+    .unwrap();
+
+    assert_eq!(
+        actions,
+        [
+            json!({ "Work.on_flag": "TEST_PATCH_FLAG" }),
+            json!({ "Work.on_flag": "TEST_PATCH_FLAG" }),
+            json!({ "Work.set_float": { "variable": "TEST_PATCH_FLOAT", "value": 3.0 } }),
+            json!({ "WaitFor": 2.0 }),
+        ]
+    );
+}
+
+#[test]
+fn nth() {
+    let actions = patch(json!([
+        { "Replace": {
+            "target": { "action": "WaitUntil", "nth": 1 },
+            "actions": [{ "WaitUntil": 12.0 }]
+        } },
+        { "Insert": {
+            "at": { "before": { "action": "WaitUntil", "nth": 0 } },
+            "actions": [{ "WaitFor": 1.0 }]
+        } }
+    ]))
+    .unwrap();
+
+    assert_eq!(actions[0], json!({ "WaitFor": 1.0 }));
+    assert_eq!(actions[1], json!({ "WaitUntil": 5.0 }));
+    assert_eq!(actions[3], json!({ "WaitUntil": 12.0 }));
+    assert_eq!(actions.len(), 6);
+}
+
+#[test]
+fn missing_target() {
+    let operations = [
+        json!({ "Remove": { "target": 5 } }),
+        json!({ "Remove": { "target": { "action": "Work.off_flag" } } }),
+        json!({ "Remove": { "target": { "action": "WaitUntil", "fields": 6.0 } } }),
+        json!({ "Remove": { "target": { "action": "WaitUntil", "nth": 2 } } }),
+    ];
+
+    for operation in operations {
+        assert!(
+            matches!(
+                patch(json!([operation])),
+                Err(ActionError::PatchError { operation: 0, .. })
+            ),
+            "{operation} applied"
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use acmd_engine::{
+    action::{ActionRegistry, DynamicAction},
+    call::Call,
+    context::set_lua_const,
+    timeline::{Timeline, TimelineError, WaitStyle},
+};
+use serde_json::{json, Value};
+
+fn registry() -> ActionRegistry {
+    set_lua_const("TEST_TIMELINE_FLAG", 1);
+
+    let registry = ActionRegistry::new();
+    registry
+        .register::<actions::WaitUntil>()
+        .unwrap()
+        .register::<actions::WaitFor>()
+        .unwrap()
+        .register::<actions::work::OnFlag>()
+        .unwrap()
+        .register_control::<actions::control::If>()
+        .unwrap()
+        .register_control::<Call>()
+        .unwrap()
+        .register::<actions::motion::SetMotionRate>()
+        .unwrap()
+        .register::<actions::motion::DesiredRate>()
+        .unwrap()
+        .register::<actions::motion::MotionRateRange>()
+        .unwrap();
+    registry
+}
+
+fn actions(registry: &ActionRegistry, values: Value) -> Vec<DynamicAction> {
+    serde_json::from_value::<Vec<Value>>(values)
+        .unwrap()
+        .into_iter()
+        .map(|value| registry.as_action(value).unwrap())
+        .collect()
+}
+
+fn values(registry: &ActionRegistry, actions: &[DynamicAction]) -> Vec<Value> {
+    actions
+        .iter()
+        .map(|action| registry.as_value(action).unwrap())
+        .collect()
+}
+
+fn flag() -> Value {
+    json!({ "Work.on_flag": "TEST_TIMELINE_FLAG" })
+}
+
+fn script(registry: &ActionRegistry) -> Vec<DynamicAction> {
+    actions(
+        registry,
+        json!([
+            { "WaitUntil": 5.0 },
+            flag(),
+            { "WaitFor": 3.0 },
+            flag(),
+            // Already passed, so it doesn't wait
+            { "WaitUntil": 4.0 },
+            flag(),
+            { "WaitFor": 2.0 }
+        ]),
+    )
+}
+
+#[test]
+fn frames() {
+    let registry = registry();
+    let mut timeline = Timeline::from_actions(&registry, script(&registry)).unwrap();
+
+    let frames: Vec<f32> = timeline.events.iter().map(|event| event.frame).collect();
+    assert_eq!(frames, [5.0, 8.0, 8.0]);
+    assert_eq!(timeline.length, 10.0);
+    assert_eq!(timeline.at(8.0).count(), 2);
+
+    let action = registry.as_action(flag()).unwrap();
+    timeline.insert(6.0, action);
+    assert_eq!(timeline.between(5.0, 8.0).count(), 2);
+    assert_eq!(timeline.events[1].frame, 6.0);
+}
+
+#[test]
+fn round_trip() {
+    let registry = registry();
+
+    let timeline = Timeline::from_actions(&registry, script(&registry)).unwrap();
+    let absolute = timeline
+        .into_actions(&registry, WaitStyle::Absolute)
+        .unwrap();
+    assert_eq!(
+        values(&registry, &absolute),
+        [
+            json!({ "WaitUntil": 5.0 }),
+            flag(),
+            json!({ "WaitUntil": 8.0 }),
+            flag(),
+            flag(),
+            json!({ "WaitUntil": 10.0 }),
+        ]
+    );
+
+    let timeline = Timeline::from_actions(&registry, absolute).unwrap();
+    let relative = timeline
+        .into_actions(&registry, WaitStyle::Relative)
+        .unwrap();
+    assert_eq!(
+        values(&registry, &relative),
+        [
+            json!({ "WaitFor": 5.0 }),
+            flag(),
+            json!({ "WaitFor": 3.0 }),
+            flag(),
+            flag(),
+            json!({ "WaitFor": 2.0 }),
+        ]
+    );
+}
+
+#[test]
+fn nested_waits() {
+    let registry = registry();
+
+    let branch = actions(
+        &registry,
+        json!([{ "If": { "condition": "IsExcute", "then": [flag()] } }]),
+    );
+    assert!(Timeline::from_actions(&registry, branch).is_ok());
+
+    let branch = actions(
+        &registry,
+        json!([flag(), { "If": { "condition": "IsExcute", "then": [{ "WaitFor": 1.0 }] } }]),
+    );
+    assert!(matches!(
+        Timeline::from_actions(&registry, branch),
+        Err(TimelineError::NestedWait { index: 1 })
+    ));
+
+    let call = actions(&registry, json!([{ "Call": { "File": "wait.acmd" } }]));
+    assert!(matches!(
+        Timeline::from_actions(&registry, call),
+        Err(TimelineError::OpaqueWait { index: 0 })
+    ));
+}
+
+/// How many frames of the game the script takes
+fn game_length(registry: &ActionRegistry, values: Value) -> f32 {
+    let timeline = Timeline::from_actions(registry, actions(registry, values)).unwrap();
+    timeline.game_length(registry)
+}
+
+#[test]
+fn motion_rate() {
+    let registry = registry();
+
+    let half = json!([{ "Motion.set_rate": 0.5 }, { "WaitUntil": 10.0 }]);
+    assert_eq!(game_length(&registry, half), 20.0);
+
+    // 5 frames at the normal rate, then 5 more at twice the speed
+    let late = json!([{ "WaitUntil": 5.0 }, { "Motion.set_rate": 2.0 }, { "WaitFor": 5.0 }]);
+    assert_eq!(game_length(&registry, late), 7.5);
+
+    let desired = json!([
+        { "Motion.desired_rate": { "frames": 10.0, "desired": 20.0 } },
+        { "WaitUntil": 10.0 }
+    ]);
+    assert_eq!(game_length(&registry, desired), 20.0);
+
+    // The range ends on frame 10, after which the rate goes back to the one set before it
+    let range = json!([
+        { "Motion.set_rate": 2.0 },
+        { "Motion.rate_range": { "start": 0.0, "end": 10.0, "desired": 20.0 } },
+        { "WaitUntil": 14.0 }
+    ]);
+    assert_eq!(game_length(&registry, range), 22.0);
+
+    let branch = json!([
+        { "If": { "condition": "IsExcute", "then": [{ "Motion.set_rate": 0.5 }] } },
+        { "WaitUntil": 4.0 }
+    ]);
+    assert_eq!(game_length(&registry, branch), 8.0);
+
+    let late = json!([{ "WaitUntil": 4.0 }, { "Motion.set_rate": 0.5 }, { "WaitUntil": 10.0 }]);
+    let timeline = Timeline::from_actions(&registry, actions(&registry, late)).unwrap();
+    assert_eq!(timeline.game_frame(&registry, 2.0), 2.0);
+    assert_eq!(timeline.game_frame(&registry, 6.0), 8.0);
+}
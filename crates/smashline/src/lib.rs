@@ -340,10 +340,12 @@ decl_imports! {
     fn smashline_get_original_acmd(fighter: &mut L2CAgentBase, name: Hash40) -> Option<AcmdFunction>;
     fn smashline_get_original_status(fighter: &mut L2CFighterBase, line: StatusLine, status: i32) -> Option<NonNull<()>>;
 
-    fn smashline_reload_script(fighter: StringFFI, weapon: StringFFI, file_name: StringFFI);
+    fn smashline_reload_script(fighter: StringFFI, weapon: StringFFI, file_name: StringFFI) -> bool;
 
     fn smashline_get_action_registry() -> &'static acmd_engine::action::ActionRegistry;
 
+    fn smashline_get_labels() -> &'static acmd_engine::RwLock<acmd_engine::labels::Labels>;
+
     fn smashline_install_acmd_script_costume(
         agent: Hash40,
         costume: Costume,
@@ -498,7 +500,13 @@ pub mod api {
         hash.hash
     }
 
-    use acmd_engine::action::Action;
+    use acmd_engine::{
+        action::{Action, ControlAction, RegistrationError},
+        labels::Labels,
+        metadata::ActionInfo,
+        observer::ExecutionObserver,
+    };
+    use std::sync::Arc;
     #[cfg(feature = "skyline_smash")]
     pub use smash as skyline_smash;
 
@@ -507,16 +515,94 @@ pub mod api {
         hash.0
     }
 
-    pub fn reload_script(fighter: &str, weapon: Option<&str>, file: &str) {
+    /// Reloads a script file of the agent, where `file` can also name a single entry of a bundle
+    /// file, like `moves.acmd#attack_11`. Returns whether it was reloaded, smashline prints the
+    /// reason when it wasn't.
+    pub fn reload_script(fighter: &str, weapon: Option<&str>, file: &str) -> bool {
         smashline_reload_script(
             StringFFI::from_str(fighter),
             StringFFI::from_str(weapon.unwrap_or("")),
             StringFFI::from_str(file),
-        );
+        )
+    }
+
+    /// Makes the plugin's copy of the engine write hashes with smashline's labels, since actions
+    /// serialize their hashes with the copy they were built with
+    fn share_labels() {
+        acmd_engine::labels::use_labels(smashline_get_labels());
+    }
+
+    /// Replaces the labels that hashes are written with, for every plugin
+    pub fn set_labels(labels: Labels) {
+        share_labels();
+        acmd_engine::labels::set_labels(labels);
+    }
+
+    /// Adds to the labels that hashes are written with, for every plugin
+    pub fn add_labels(labels: Labels) {
+        share_labels();
+        acmd_engine::labels::add_labels(labels);
+    }
+
+    pub fn register_action<A: Action>() -> Result<(), RegistrationError> {
+        share_labels();
+        smashline_get_action_registry().register::<A>().map(|_| ())
+    }
+
+    /// Registers an action under a namespace, usually the plugin's name, so that actions from
+    /// different plugins can share a name
+    pub fn register_action_in<A: Action>(namespace: &str) -> Result<(), RegistrationError> {
+        share_labels();
+        smashline_get_action_registry()
+            .register_in::<A>(namespace)
+            .map(|_| ())
+    }
+
+    pub fn register_control_action<C: ControlAction>() -> Result<(), RegistrationError> {
+        share_labels();
+        smashline_get_action_registry()
+            .register_control::<C>()
+            .map(|_| ())
+    }
+
+    pub fn register_control_action_in<C: ControlAction>(
+        namespace: &str,
+    ) -> Result<(), RegistrationError> {
+        share_labels();
+        smashline_get_action_registry()
+            .register_control_in::<C>(namespace)
+            .map(|_| ())
+    }
+
+    pub fn unregister_action(name: &str) -> Result<(), RegistrationError> {
+        smashline_get_action_registry().unregister(name)
+    }
+
+    /// Removes every action registered under the namespace, for plugins that are about to unload
+    pub fn unregister_action_namespace(namespace: &str) {
+        smashline_get_action_registry().unregister_namespace(namespace);
+    }
+
+    /// Lets scripts use `alias` as another name for the action registered as `name`
+    pub fn alias_action(alias: &str, name: &str) -> Result<(), RegistrationError> {
+        smashline_get_action_registry()
+            .alias(alias, name)
+            .map(|_| ())
+    }
+
+    /// Every action that scripts can use, with the descriptions of the actions and their fields
+    pub fn registered_actions() -> Vec<ActionInfo> {
+        smashline_get_action_registry().actions()
+    }
+
+    /// Lets the observer see every script and action run by smashline, see
+    /// [`acmd_engine::observer`]
+    pub fn add_execution_observer<O: ExecutionObserver + 'static>(observer: Arc<O>) {
+        smashline_get_action_registry().add_observer(observer);
     }
 
-    pub fn register_action<A: Action>() {
-        smashline_get_action_registry().register::<A>();
+    pub fn remove_execution_observer(observer: &Arc<dyn ExecutionObserver>) -> bool {
+        smashline_get_action_registry().remove_observer(observer)
     }
 
     pub fn install_status_script_costume(
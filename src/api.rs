@@ -3,7 +3,7 @@ use std::{
     ptr::NonNull,
 };
 
-use acmd_engine::action::ActionRegistry;
+use acmd_engine::{action::ActionRegistry, labels::Labels};
 use rtld::Section;
 use smashline::{
     Acmd, AcmdFunction, AgentEntry, Costume, Hash40, L2CAgentBase, ObjectEvent, Priority, StatusLine, StringFFI,
@@ -79,6 +79,9 @@ pub extern "C" fn smashline_remove_by_plugin_range(start: usize, end: usize) {
             .filter(|cb| !(start..end).contains(&(cb.function as *const () as usize)))
             .collect();
     }
+
+    crate::interpreter::ACTION_REGISTRY.unregister_range(start..end);
+    crate::interpreter::ACTION_REGISTRY.remove_observers_range(start..end);
 }
 
 #[no_mangle]
@@ -284,20 +287,31 @@ pub extern "C" fn smashline_get_action_registry() -> &'static ActionRegistry {
     &crate::interpreter::ACTION_REGISTRY
 }
 
+#[no_mangle]
+pub extern "C" fn smashline_get_labels() -> &'static acmd_engine::RwLock<Labels> {
+    acmd_engine::labels::labels()
+}
+
 #[no_mangle]
 pub extern "C" fn smashline_reload_script(
     fighter: StringFFI,
     weapon: StringFFI,
     file_name: StringFFI,
-) {
+) -> bool {
     let fighter = fighter.as_str().unwrap();
     let weapon = weapon.as_str().unwrap();
     let file_name = file_name.as_str().unwrap();
-    crate::interpreter::load_single_script(
+    let result = crate::interpreter::load_single_script(
         fighter,
         (!weapon.is_empty()).then_some(weapon),
         file_name,
     );
+
+    if let Err(e) = result.as_ref() {
+        println!("Failed to reload {file_name}: {e}");
+    }
+
+    result.is_ok()
 }
 
 #[no_mangle]
@@ -5,7 +5,7 @@ use std::{
     }, time::Duration
 };
 
-use acmd_engine::SmashlineScript;
+use acmd_engine::ScriptHandle;
 use skyline::hooks::InlineCtx;
 use smash::{
     app::{BattleObject, BattleObjectModuleAccessor},
@@ -260,13 +260,7 @@ fn install_script(
     let acmd_scripts = acmd_scripts.read();
     if let Some(scripts) = acmd_scripts.get(&entry) {
         for (hash, script) in scripts.get_scripts(acmd) {
-            let c = script.costume.as_slice();
-
-            if has_costume && !c.contains(&(costume as usize)) {
-                continue;
-            }
-
-            if !has_costume && !c.is_empty() {
+            if !crate::utils::is_for_costume(script.costume.as_slice(), costume, has_costume) {
                 continue;
             }
 
@@ -384,7 +378,7 @@ pub(crate) extern "C" fn unreachable_smashline_script(
 
 pub enum UserScript {
     Function(unsafe extern "C" fn(&mut L2CAgentBase)),
-    Script(Arc<locks::RwLock<Arc<SmashlineScript>>>),
+    Script(ScriptHandle),
 }
 
 #[derive(Default)]
@@ -529,10 +523,13 @@ fn create_agent_hook(
             let mut user_scripts = HashMap::new();
 
             let smashline_scripts = crate::interpreter::get_or_load_scripts(name, None);
-            for script in smashline_scripts
-                .iter()
-                .filter(|script| acmd == script.script.read().category)
-            {
+            let costume = crate::utils::get_agent_costume(object as *const BattleObject).unwrap_or(0);
+            let has_costume = crate::interpreter::has_costume(&smashline_scripts, costume);
+            for script in smashline_scripts.iter().filter(|script| {
+                let script = script.script.read();
+                acmd == script.category
+                    && crate::utils::is_for_costume(&script.costumes, costume, has_costume)
+            }) {
                 agent.sv_set_function_hash(
                     unsafe { std::mem::transmute(unreachable_smashline_script as *const ()) },
                     script.script.read().replace.as_hash40(),
@@ -619,10 +616,13 @@ fn create_agent_hook(
                 .collect();
 
             let smashline_scripts = crate::interpreter::get_or_load_scripts(&owner, Some(&name));
-            for script in smashline_scripts
-                .iter()
-                .filter(|script| acmd == script.script.read().category)
-            {
+            let costume = crate::utils::get_agent_costume(object as *const BattleObject).unwrap_or(0);
+            let has_costume = crate::interpreter::has_costume(&smashline_scripts, costume);
+            for script in smashline_scripts.iter().filter(|script| {
+                let script = script.script.read();
+                acmd == script.category
+                    && crate::utils::is_for_costume(&script.costumes, costume, has_costume)
+            }) {
                 agent.sv_set_function_hash(
                     unsafe { std::mem::transmute(unreachable_smashline_script as *const ()) },
                     script.script.read().replace.as_hash40(),
@@ -747,13 +747,7 @@ fn install_status_scripts(
     let mut max_new = old_total;
 
     for status in list.iter() {
-        let c = status.costume.as_slice();
-
-        if has_costume && !c.contains(&(costume as usize)) {
-            continue;
-        }
-
-        if !has_costume && !c.is_empty() {
+        if !crate::utils::is_for_costume(status.costume.as_slice(), costume, has_costume) {
             continue;
         }
 
@@ -873,13 +867,7 @@ extern "C" fn set_status_scripts(agent: &mut L2CFighterWrapper) {
 
     for callback in callbacks.iter() {
         if callback.hash == Some(hash) {
-            let c = callback.costume.as_slice();
-
-            if has_costume && !c.contains(&(costume as usize)) {
-                continue;
-            }
-
-            if !has_costume && !c.is_empty() {
+            if !crate::utils::is_for_costume(callback.costume.as_slice(), costume, has_costume) {
                 continue;
             }
 
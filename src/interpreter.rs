@@ -1,13 +1,21 @@
 use std::{
     collections::BTreeMap,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{Arc, Weak},
 };
 
-use acmd_engine::{action::ActionRegistry, SmashlineScript};
+use acmd_engine::{
+    action::{ActionError, ActionRegistry},
+    asset::{LatestScriptPatch, LatestScriptTemplate},
+    call::Call,
+    labels::Labels,
+    template::Templates,
+    ScriptFile, ScriptHandle, SmashlineScript,
+};
 use locks::Mutex;
 use skyline::hooks::InlineCtx;
 use smashline::{Hash40, L2CAgentBase, Variadic};
+use thiserror::Error;
 
 use crate::create_agent::{unreachable_smashline_script, UserScript};
 
@@ -28,10 +36,130 @@ extern "C" {
 
 std::arch::global_asm!(include_str!("interpreter.asm"));
 
-pub fn get_scripts(fighter_name: &str, weapon_name: Option<&str>) -> Vec<LoadedScript> {
-    let sub_folder = weapon_name.unwrap_or("body");
-    let path = PathBuf::from(format!("mods:/fighter/{fighter_name}/acmd/{sub_folder}/"));
-    let read_dir = match std::fs::read_dir(&path) {
+#[derive(Error, Debug)]
+pub enum LoadError {
+    #[error("Failed to read {path}: {error}")]
+    Read { path: String, error: std::io::Error },
+
+    #[error("Failed to parse {path}: {error}")]
+    Parse { path: String, error: ActionError },
+
+    #[error("Failed to parse {path}: entry '{entry}' is for agent '{agent}', not '{expected}'")]
+    WrongAgent {
+        path: String,
+        entry: String,
+        agent: String,
+        expected: String,
+    },
+
+    #[error("{0} is not a smashline script")]
+    NotAScript(String),
+
+    #[error("{file} has no entry '{entry}'")]
+    MissingEntry { file: String, entry: String },
+
+    #[error("{file} defines template '{template}', reload the scripts that use it instead")]
+    Template { file: String, template: String },
+
+    #[error("{0} wasn't loaded with the rest of the agent's scripts, so it can't be reloaded")]
+    NotLoaded(String),
+
+    #[error("No loaded scripts to insert into")]
+    NoLoadedScripts,
+}
+
+#[derive(Copy, Clone)]
+enum ScriptFormat {
+    Json,
+    Binary,
+    Text,
+}
+
+impl ScriptFormat {
+    fn from_path(path: &Path) -> Option<Self> {
+        match path.extension().and_then(|s| s.to_str()) {
+            Some("acmd") => Some(Self::Json),
+            Some("acmdb") => Some(Self::Binary),
+            Some("acmdl") => Some(Self::Text),
+            _ => None,
+        }
+    }
+}
+
+fn read_script(
+    path: &Path,
+    format: ScriptFormat,
+    templates: &Templates,
+) -> Result<ScriptFile, LoadError> {
+    let script = match format {
+        ScriptFormat::Json => std::fs::read_to_string(path)
+            .map(|data| ScriptFile::from_json(&ACTION_REGISTRY, templates, data)),
+        ScriptFormat::Binary => std::fs::read(path)
+            .map(|data| ScriptFile::from_binary(&ACTION_REGISTRY, templates, data)),
+        ScriptFormat::Text => std::fs::read_to_string(path)
+            .map(|data| ScriptFile::from_text(&ACTION_REGISTRY, templates, data)),
+    };
+
+    let path = path.display().to_string();
+    match script {
+        Ok(Ok(script)) => Ok(script),
+        Ok(Err(error)) => Err(LoadError::Parse { path, error }),
+        Err(error) => Err(LoadError::Read { path, error }),
+    }
+}
+
+/// Reads the template defined by a file, errors are reported once the file is read as a script
+fn read_template(path: &Path, format: ScriptFormat) -> Option<LatestScriptTemplate> {
+    match format {
+        ScriptFormat::Json => std::fs::read_to_string(path)
+            .ok()
+            .and_then(|data| Templates::read_json(data).ok().flatten()),
+        ScriptFormat::Binary => std::fs::read(path)
+            .ok()
+            .and_then(|data| Templates::read_binary(data).ok().flatten()),
+        ScriptFormat::Text => None,
+    }
+}
+
+/// Reads the patch defined by a file, errors are reported once the file is read as a script
+fn read_patch(
+    path: &Path,
+    format: ScriptFormat,
+    templates: &Templates,
+) -> Option<LatestScriptPatch> {
+    let patch = match format {
+        ScriptFormat::Json => std::fs::read_to_string(path)
+            .ok()
+            .and_then(|data| LatestScriptPatch::read_json(data).ok().flatten()),
+        ScriptFormat::Binary => std::fs::read(path)
+            .ok()
+            .and_then(|data| LatestScriptPatch::read_binary(data).ok().flatten()),
+        ScriptFormat::Text => None,
+    }?;
+
+    patch.validate(&ACTION_REGISTRY, templates).ok()?;
+    Some(patch)
+}
+
+/// The costume slot of a `c00`, `c01`, ... folder
+fn costume_folder(name: &str) -> Option<usize> {
+    let slot = name.strip_prefix('c')?;
+    if slot.len() < 2 || !slot.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    slot.parse().ok()
+}
+
+/// The costume slot of the folder a script file is in, `None` for the agent's folder itself
+fn file_costume(file_name: &str) -> Option<usize> {
+    file_name
+        .split_once('/')
+        .and_then(|(folder, _)| costume_folder(folder))
+}
+
+/// Lists the script files of a folder, named with `prefix` in front of their file name
+fn list_files(path: &Path, prefix: &str) -> Vec<(PathBuf, String, ScriptFormat)> {
+    let read_dir = match std::fs::read_dir(path) {
         Ok(read_dir) => read_dir,
         Err(e) => {
             // println!("Failed to get scripts: {e}");
@@ -39,7 +167,7 @@ pub fn get_scripts(fighter_name: &str, weapon_name: Option<&str>) -> Vec<LoadedS
         }
     };
 
-    let mut scripts = vec![];
+    let mut files = vec![];
 
     for entry in read_dir {
         let entry = match entry {
@@ -50,92 +178,348 @@ pub fn get_scripts(fighter_name: &str, weapon_name: Option<&str>) -> Vec<LoadedS
             }
         };
 
+        let name = entry.file_name().to_string_lossy().to_string();
+
         match entry.file_type() {
             Ok(ty) if ty.is_file() => {}
+            Ok(ty) if ty.is_dir() && prefix.is_empty() && costume_folder(&name).is_some() => {
+                files.extend(list_files(&entry.path(), &format!("{name}/")));
+                continue;
+            }
             _ => continue,
         }
 
-        let Some("acmd") = entry.path().extension().and_then(|s| s.to_str()) else {
+        let Some(format) = ScriptFormat::from_path(&entry.path()) else {
             continue;
         };
 
-        let data = match std::fs::read_to_string(entry.path()) {
-            Ok(data) => data,
-            Err(e) => {
-                println!("Failed to read {}: {e}", entry.path().display());
-                continue;
+        files.push((entry.path(), format!("{prefix}{name}"), format));
+    }
+
+    files
+}
+
+/// Everything read from the scripts folder of an agent.
+///
+/// Files in a `c00`, `c01`, ... folder are named with the folder in front, like `c03/attack_11.acmd`,
+/// and the scripts of a bundle are named after the bundle and their entry, like `moves.acmd#attack_11`.
+/// Scripts in those folders are only installed for that costume unless they list their own
+/// costumes, and patches in them only apply to the scripts of the same folder.
+#[derive(Default)]
+struct ScriptFolder {
+    scripts: Vec<(String, SmashlineScript)>,
+
+    /// Sorted by file name, so that patches touching the same script are applied in a fixed order
+    patches: Vec<(String, LatestScriptPatch)>,
+
+    templates: Templates,
+}
+
+impl ScriptFolder {
+    /// Reads the folder of `agent`, leaving out bundles which are for another agent
+    fn read(path: &Path, agent: Hash40) -> Self {
+        let files = list_files(path, "");
+
+        let mut folder = Self::read_shared(&files);
+
+        for (path, file_name, format) in files {
+            let file = match read_script(&path, format, &folder.templates) {
+                Ok(file) => file,
+                Err(e) => {
+                    println!("{e}");
+                    continue;
+                }
+            };
+
+            match file {
+                ScriptFile::Script(script) => folder.push_script(file_name, script),
+                ScriptFile::Bundle(scripts) => {
+                    match bundle_scripts(&path, &file_name, agent, scripts) {
+                        Ok(scripts) => {
+                            for (name, script) in scripts {
+                                folder.push_script(name, script);
+                            }
+                        }
+                        Err(e) => println!("{e}"),
+                    }
+                }
+                // Both were already read with the rest of the folder's shared files
+                ScriptFile::Patch(_) | ScriptFile::Template(_) => {}
             }
-        };
+        }
 
-        let script = match SmashlineScript::from_json(&ACTION_REGISTRY, data) {
-            Ok(data) => data,
-            Err(e) => {
-                println!("Failed to parse {}: {e}", entry.path().display());
-                continue;
+        folder
+    }
+
+    /// Reads only the templates and patches of a folder, which apply to the scripts of every file
+    /// in it
+    fn read_shared(files: &[(PathBuf, String, ScriptFormat)]) -> Self {
+        let mut folder = Self::default();
+
+        // Templates are read first, since any file in the folder can instantiate them
+        for (path, _, format) in files.iter() {
+            if let Some(template) = read_template(path, *format) {
+                folder.templates.insert(template);
             }
-        };
+        }
+
+        for (path, file_name, format) in files.iter() {
+            if let Some(patch) = read_patch(path, *format, &folder.templates) {
+                folder.patches.push((file_name.clone(), patch));
+            }
+        }
 
-        scripts.push(LoadedScript {
-            file_name: entry
-                .path()
-                .file_name()
-                .unwrap()
-                .to_string_lossy()
-                .to_string(),
-            script: Arc::new(locks::RwLock::new(Arc::new(script))),
+        folder.patches.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        folder
+    }
+
+    fn push_script(&mut self, file_name: String, script: SmashlineScript) {
+        let script = with_folder_costume(&file_name, script);
+        self.scripts.push((file_name, script));
+    }
+
+    /// Applies every patch that targets the script, skipping the ones that fail
+    fn apply_patches(&self, file_name: &str, script: SmashlineScript) -> SmashlineScript {
+        self.patches
+            .iter()
+            .filter(|(patch_name, patch)| patch_applies(patch_name, patch, file_name, &script))
+            .fold(script, |script, (patch_name, patch)| {
+                match patch.apply(&ACTION_REGISTRY, &self.templates, &script) {
+                    Ok(patched) => patched,
+                    Err(e) => {
+                        println!("Failed to apply {patch_name} to {file_name}: {e}");
+                        script
+                    }
+                }
+            })
+    }
+}
+
+/// Whether the patch of `patch_name` applies to the script of `file_name`, patches in a costume
+/// folder only apply to the scripts of the same folder
+fn patch_applies(
+    patch_name: &str,
+    patch: &LatestScriptPatch,
+    file_name: &str,
+    script: &SmashlineScript,
+) -> bool {
+    let patch_costume = file_costume(patch_name);
+    patch.targets(script) && (patch_costume.is_none() || patch_costume == file_costume(file_name))
+}
+
+/// Installs the scripts of a costume folder only for that costume, unless they list their own
+fn with_folder_costume(file_name: &str, mut script: SmashlineScript) -> SmashlineScript {
+    if let Some(costume) = file_costume(file_name) {
+        if script.costumes.is_empty() {
+            script.costumes.push(costume);
+        }
+    }
+    script
+}
+
+/// Names the scripts of a bundle after the bundle and their entry, failing if any of them is for
+/// another agent than the folder's
+fn bundle_scripts(
+    path: &Path,
+    file_name: &str,
+    agent: Hash40,
+    scripts: Vec<(String, SmashlineScript)>,
+) -> Result<Vec<(String, SmashlineScript)>, LoadError> {
+    if let Some((entry, script)) = scripts.iter().find(|(_, script)| script.agent != agent) {
+        return Err(LoadError::WrongAgent {
+            path: path.display().to_string(),
+            entry: entry.clone(),
+            agent: acmd_engine::labels::to_string(script.agent),
+            expected: acmd_engine::labels::to_string(agent),
         });
     }
 
-    scripts
+    Ok(scripts
+        .into_iter()
+        .map(|(entry, script)| (format!("{file_name}#{entry}"), script))
+        .collect())
 }
 
-pub fn load_single_script(fighter: &str, weapon_name: Option<&str>, file_name: &str) {
+fn script_folder(fighter_name: &str, weapon_name: Option<&str>) -> PathBuf {
     let sub_folder = weapon_name.unwrap_or("body");
-    let path = PathBuf::from(format!(
-        "mods:/fighter/{fighter}/acmd/{sub_folder}/{file_name}"
-    ));
-    let data = match std::fs::read_to_string(&path) {
-        Ok(data) => data,
-        Err(e) => {
-            println!("Failed to read {}: {e}", path.display());
-            return;
-        }
+    PathBuf::from(format!("mods:/fighter/{fighter_name}/acmd/{sub_folder}/"))
+}
+
+fn agent_hash(fighter_name: &str, weapon_name: Option<&str>) -> Hash40 {
+    if let Some(weapon) = weapon_name {
+        Hash40::new(fighter_name).concat_str("_").concat_str(weapon)
+    } else {
+        Hash40::new(fighter_name)
+    }
+}
+
+/// Whether any of an agent's data scripts are for `costume`, in which case it only gets the data
+/// scripts for that costume.
+///
+/// This is kept apart from the costumes marked through the API on purpose: marking a costume there
+/// would leave out every script, status script and callback that other plugins installed for all
+/// costumes, just because a data script exists for it.
+pub fn has_costume(scripts: &[LoadedScript], costume: i32) -> bool {
+    scripts
+        .iter()
+        .any(|script| script.script.read().costumes.contains(&(costume as usize)))
+}
+
+pub fn get_scripts(fighter_name: &str, weapon_name: Option<&str>) -> Vec<LoadedScript> {
+    let agent = agent_hash(fighter_name, weapon_name);
+    let mut folder = ScriptFolder::read(&script_folder(fighter_name, weapon_name), agent);
+
+    let scripts: Vec<_> = std::mem::take(&mut folder.scripts)
+        .into_iter()
+        .map(|(file_name, script)| {
+            let script = folder.apply_patches(&file_name, script);
+            LoadedScript {
+                file_name,
+                script: Arc::new(locks::RwLock::new(Arc::new(script))),
+            }
+        })
+        .collect();
+
+    link_scripts(&scripts);
+
+    scripts
+}
+
+fn link_scripts(scripts: &[LoadedScript]) {
+    let errors = acmd_engine::call::link_scripts(
+        &ACTION_REGISTRY,
+        scripts
+            .iter()
+            .map(|script| (script.file_name.as_str(), &script.script)),
+    );
+
+    for error in errors {
+        println!("Failed to link scripts: {error}");
+    }
+}
+
+/// What reloading a file reads from it
+enum Reloaded {
+    Scripts(Vec<(String, SmashlineScript)>),
+    Patch(LatestScriptPatch),
+}
+
+/// Reads the scripts of a file again, or only one of them when `file_name` names an entry of a
+/// bundle like `moves.acmd#attack_11`
+fn read_reloaded(
+    folder_path: &Path,
+    folder: &ScriptFolder,
+    agent: Hash40,
+    file_name: &str,
+) -> Result<Reloaded, LoadError> {
+    let (file, entry) = match file_name.split_once('#') {
+        Some((file, entry)) => (file, Some(entry)),
+        None => (file_name, None),
     };
 
-    let new_script = match SmashlineScript::from_json(&ACTION_REGISTRY, data) {
-        Ok(data) => data,
-        Err(e) => {
-            println!("Failed to parse {}: {e}", path.display());
-            return;
-        }
+    let path = folder_path.join(file);
+    let format = ScriptFormat::from_path(&path)
+        .ok_or_else(|| LoadError::NotAScript(path.display().to_string()))?;
+
+    let missing_entry = |entry: &str| LoadError::MissingEntry {
+        file: file.to_string(),
+        entry: entry.to_string(),
     };
 
-    let agent = if let Some(weapon) = weapon_name {
-        Hash40::new(fighter).concat_str("_").concat_str(weapon)
-    } else {
-        Hash40::new(fighter)
+    let scripts = match (read_script(&path, format, &folder.templates)?, entry) {
+        (ScriptFile::Script(script), None) => vec![(file_name.to_string(), script)],
+        (ScriptFile::Bundle(mut scripts), entry) => {
+            if let Some(entry) = entry {
+                scripts.retain(|(name, _)| name == entry);
+                if scripts.is_empty() {
+                    return Err(missing_entry(entry));
+                }
+            }
+            bundle_scripts(&path, file, agent, scripts)?
+        }
+        (ScriptFile::Patch(patch), None) => return Ok(Reloaded::Patch(patch)),
+        (ScriptFile::Template(template), None) => {
+            return Err(LoadError::Template {
+                file: file.to_string(),
+                template: template.template,
+            })
+        }
+        (_, Some(entry)) => return Err(missing_entry(entry)),
     };
 
+    Ok(Reloaded::Scripts(
+        scripts
+            .into_iter()
+            .map(|(name, script)| {
+                let script = with_folder_costume(&name, script);
+                (name, script)
+            })
+            .collect(),
+    ))
+}
+
+/// Reloads the script of a file, every script of a bundle file, or a single entry of a bundle when
+/// `file_name` is written like `moves.acmd#attack_11`.
+///
+/// Only the templates and patches of the rest of the folder are read again. Reloading a patch
+/// reloads the scripts it now targets, since the previous version of the patch can't be undone.
+/// Templates can't be reloaded on their own, the scripts that use them have to be reloaded instead.
+pub fn load_single_script(
+    fighter: &str,
+    weapon_name: Option<&str>,
+    file_name: &str,
+) -> Result<(), LoadError> {
+    let folder_path = script_folder(fighter, weapon_name);
+    let agent = agent_hash(fighter, weapon_name);
+    let folder = ScriptFolder::read_shared(&list_files(&folder_path, ""));
+
     let loaded_scripts = LOADED_SCRIPTS.write();
-    let Some(loaded) = loaded_scripts.get(&agent).and_then(|weak| weak.upgrade()) else {
-        println!("No loaded scripts to insert into");
-        return;
-    };
+    let loaded = loaded_scripts
+        .get(&agent)
+        .and_then(|weak| weak.upgrade())
+        .ok_or(LoadError::NoLoadedScripts)?;
+
+    let scripts = match read_reloaded(&folder_path, &folder, agent, file_name)? {
+        Reloaded::Scripts(scripts) => scripts,
+        Reloaded::Patch(patch) => {
+            let mut scripts = vec![];
+            for script in loaded.iter() {
+                if !patch_applies(file_name, &patch, &script.file_name, &script.script.read()) {
+                    continue;
+                }
 
-    for script in loaded.iter() {
-        if script.file_name == file_name {
-            *script.script.write() = Arc::new(new_script);
-            return;
+                if let Reloaded::Scripts(reloaded) =
+                    read_reloaded(&folder_path, &folder, agent, &script.file_name)?
+                {
+                    scripts.extend(reloaded);
+                }
+            }
+            scripts
         }
+    };
+
+    // Scripts are only installed when the agent is first loaded, so new ones can't be added here
+    let mut replaced = Vec::with_capacity(scripts.len());
+    for (name, script) in scripts {
+        let Some(target) = loaded.iter().find(|loaded| loaded.file_name == name) else {
+            return Err(LoadError::NotLoaded(name));
+        };
+        replaced.push((target, folder.apply_patches(&name, script)));
     }
+
+    for (target, script) in replaced {
+        *target.script.write() = Arc::new(script);
+    }
+
+    // The new script's calls haven't been linked yet, and it might introduce a cycle
+    link_scripts(&loaded);
+
+    Ok(())
 }
 
 pub fn get_or_load_scripts(fighter: &str, weapon_name: Option<&str>) -> Arc<Vec<LoadedScript>> {
-    let agent = if let Some(weapon) = weapon_name {
-        Hash40::new(fighter).concat_str("_").concat_str(weapon)
-    } else {
-        Hash40::new(fighter)
-    };
+    let agent = agent_hash(fighter, weapon_name);
 
     let mut loaded_scripts = LOADED_SCRIPTS.write();
     if let Some(loaded) = loaded_scripts.get(&agent).and_then(|weak| weak.upgrade()) {
@@ -150,7 +534,7 @@ pub fn get_or_load_scripts(fighter: &str, weapon_name: Option<&str>) -> Arc<Vec<
 
 pub struct LoadedScript {
     pub file_name: String,
-    pub script: Arc<locks::RwLock<Arc<SmashlineScript>>>,
+    pub script: ScriptHandle,
 }
 
 pub static ACTION_REGISTRY: ActionRegistry = ActionRegistry::new();
@@ -176,13 +560,13 @@ extern "C" fn smashline_interpreter(
             }
             UserScript::Script(script) => {
                 let script = script.read().clone();
-                for action in script.actions.iter() {
-                    if let Err(e) = ACTION_REGISTRY
-                        .execute(action, unsafe { std::mem::transmute(agent as *mut _) })
-                    {
-                        println!("Failed to run action '{}': {e}", action.name);
-                    }
-                }
+                let context = unsafe {
+                    &mut *(agent as *mut L2CAgentBase
+                        as *mut smashline::skyline_smash::lua2cpp::L2CAgentBase)
+                };
+                ACTION_REGISTRY.execute_script(&script, context, |action, e| {
+                    println!("Failed to run action '{}': {e}", action.name)
+                });
             }
         }
     } else {
@@ -259,7 +643,27 @@ unsafe fn call_by_hash_hook(ctx: &mut InlineCtx) {
     }
 }
 
+/// The labels that hashes are written with, shared with every plugin through
+/// `smashline_get_labels`
+const LABELS_PATH: &str = "sd:/ultimate/smashline/ParamLabels.csv";
+
+fn load_labels() {
+    let Ok(csv) = std::fs::read_to_string(LABELS_PATH) else {
+        return;
+    };
+
+    match Labels::from_csv(&csv) {
+        Ok(labels) => acmd_engine::labels::set_labels(labels),
+        Err(e) => println!("Failed to read {LABELS_PATH}: {e}"),
+    }
+}
+
 pub fn install() {
+    load_labels();
+
+    if let Err(e) = ACTION_REGISTRY.register_control::<Call>() {
+        println!("Failed to register the Call action: {e}");
+    }
     skyline::install_hooks!(call_by_hash_hook, call_function_by_hash);
 }
 
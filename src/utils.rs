@@ -74,6 +74,16 @@ pub fn has_costume(hash: Hash40, costume: i32) -> bool {
         })
 }
 
+/// Whether a script for `costumes` is installed on an agent wearing `costume`. Agents wearing a
+/// costume that something was installed for only get the scripts for that costume.
+pub fn is_for_costume(costumes: &[usize], costume: i32, has_costume: bool) -> bool {
+    if has_costume {
+        costumes.contains(&(costume as usize))
+    } else {
+        costumes.is_empty()
+    }
+}
+
 pub fn get_costume_data(hash: Hash40, costume: i32) -> Costume {
     let def = Costume::default();
     COSTUMES